@@ -100,16 +100,12 @@
                 inhibitory-population-id)))))
 
 (defun set-input-layer-firing-freq (image-as-pixel-array &key (intensity-plus 0))
-  (dotimes (i 28)
-    (dotimes (j 28)
-      (let ((pixel (aref image-as-pixel-array i j)))
-        (network-set-static-poisson-freq (+ (first (getf *input-layer-population*
-                                                         :|neuron_ids|))
-                                            (* i 28)
-                                            j)
-                                         (coerce (* (/ pixel 8)
-                                                    (+ *start-intensity* intensity-plus))
-                                                 'double-float))))))
+  (network-set-rates (getf *input-layer-population* :|id|)
+                     (loop for i from 0 below 28
+                           append (loop for j from 0 below 28
+                                        collect (coerce (* (/ (aref image-as-pixel-array i j) 8)
+                                                           (+ *start-intensity* intensity-plus))
+                                                        'double-float)))))
 
 (defun neuron-firing-count (neuron-spike-record)
   (length (first (second neuron-spike-record))))
@@ -174,13 +170,8 @@
                                 "Inhibitory")
         (network-record-spikes excitatory-population-id)
         (loop-run-image-input excitatory-population-id image-label "training" nil
-          (dotimes (i 28)
-            (dotimes (j 28)
-              (network-set-static-poisson-freq (+ (first (getf *input-layer-population*
-                                                               :|neuron_ids|))
-                                                  (* i 28)
-                                                  j)
-                                               0d0)))
+          (network-set-rates input-population-id
+                             (make-list 784 :initial-element 0d0))
           (network-set-property excitatory-population-id
                                 "fix_theta"
                                 1d0)
@@ -4,9 +4,13 @@
    :hello-world
    :sum
    :network-create
+   :network-neuron-models
+   :network-neuron-model-parameters
    :network-connect
    :network-static-connect
    :network-stdp-connect
+   :network-connect-spec
+   :network-connect-current
    :network-record-spikes
    :network-clear-spike-records
    :network-get-spike-records
@@ -14,10 +18,39 @@
    :network-set-property
    :network-set-properties
    :network-get-property
+   :network-set-spike-times
+   :network-load-spike-times
+   :network-get-property-names
+   :network-set-integration
+   :network-last-error
    :network-get-conn-info-by-id
    :network-run
+   :network-set-precise-timing
    :network-get-population-by-id
+   :network-slice-population
+   :network-select-population
+   :network-assemble
    :network-set-static-poisson-freq
+   :network-set-rates
+   :network-set-rate-schedule
+   :network-encode
+   :network-normalize-incoming-weights
+   :network-normalize-population-inputs
+   :network-add-weight-normalization
+   :network-clear-weight-normalizations
+   :network-add-threshold-adaptation
+   :network-add-synaptic-scaling
+   :network-clear-synaptic-scalings
+   :network-set-plasticity
+   :network-set-projection-plasticity
+   :network-reset-state
+   :network-set-engine
+   :network-create-group
+   :network-run-with-callback
+   :network-add-step-hook
+   :network-remove-step-hook
+   :network-clear-step-hooks
+   :network-run-until
    ))
 (in-package :cl-wheatnnleek-cffi/ffi)
 ;;;don't edit above
@@ -53,7 +86,41 @@
                 (list :|population| (jonathan:parse string))))
       (%json_string_free p))))
 
-(cffi:defcfun ("Network_clear" network-clear) :void)
+(cffi:defcfun ("Network_create_group" %network-create-group) :pointer
+  (neuron_number :int)
+  (model :string)
+  (params :string))
+
+(defun network-create-group (neuron-number model params-plist)
+  (let ((p (%network-create-group neuron-number model (jonathan:to-json params-plist))))
+    (unwind-protect
+         (let ((string (cffi:foreign-string-to-lisp p)))
+           (and string
+                (list :|population| (jonathan:parse string))))
+      (%json_string_free p))))
+
+(cffi:defcfun ("Network_neuron_models" %network-neuron-models) :pointer)
+
+(defun network-neuron-models ()
+  (let ((p (%network-neuron-models)))
+    (unwind-protect
+         (let ((string (cffi:foreign-string-to-lisp p)))
+           (and string
+                (jonathan:parse string)))
+      (%json_string_free p))))
+
+(cffi:defcfun ("Network_neuron_model_parameters" %network-neuron-model-parameters) :pointer
+  (model :string))
+
+(defun network-neuron-model-parameters (model)
+  (let ((p (%network-neuron-model-parameters model)))
+    (unwind-protect
+         (let ((string (cffi:foreign-string-to-lisp p)))
+           (and string
+                (jonathan:parse string)))
+      (%json_string_free p))))
+
+(cffi:defcfun ("Network_clear" network-clear) :bool)
 
 (cffi:defcfun ("Network_connect" %network-connect) :pointer
   (pop_id1 :int)
@@ -117,6 +184,19 @@
                 (jonathan:parse string)))
       (%json_string_free p))))
 
+(cffi:defcfun ("Network_connect_spec" %network-connect-spec) :pointer
+  (pop_id1 :int)
+  (pop_id2 :int)
+  (spec :string))
+
+(defun network-connect-spec (pop-id1 pop-id2 spec-plist)
+  (let ((p (%network-connect-spec pop-id1 pop-id2 (jonathan:to-json spec-plist))))
+    (unwind-protect
+         (let ((string (cffi:foreign-string-to-lisp p)))
+           (and string
+                (jonathan:parse string)))
+      (%json_string_free p))))
+
 (cffi:defcfun ("Network_record_spikes" network-record-spikes) :bool
   (population_id :int))
 
@@ -137,12 +217,12 @@
   (conn-id :int)
   (weight :double))
 
-(cffi:defcfun ("Network_set_property" network-set-property) :void
+(cffi:defcfun ("Network_set_property" network-set-property) :bool
   (pop_id :int)
   (name :string)
   (value :double))
 
-(cffi:defcfun ("Network_set_properties" %network-set-properties) :void
+(cffi:defcfun ("Network_set_properties" %network-set-properties) :bool
   (pop_id :int)
   (name :string)
   (value :string))
@@ -150,6 +230,17 @@
 (defun network-set-properties (pop-id name value)
   (%network-set-properties pop-id name (jonathan:to-json value)))
 
+(cffi:defcfun ("Network_set_spike_times" %network-set-spike-times) :bool
+  (pop_id :int)
+  (times :string))
+
+(defun network-set-spike-times (pop-id times)
+  (%network-set-spike-times pop-id (jonathan:to-json times)))
+
+(cffi:defcfun ("Network_load_spike_times" network-load-spike-times) :bool
+  (pop_id :int)
+  (path :string))
+
 (cffi:defcfun ("Network_get_property" %network-get-property) :pointer
   (pop_id :int)
   (name :string))
@@ -162,6 +253,29 @@
                 (jonathan:parse string)))
       (%json_string_free p))))
 
+(cffi:defcfun ("Network_get_property_names" %network-get-property-names) :pointer
+  (neuron_id :int))
+
+(defun network-get-property-names (neuron-id)
+  (let ((p (%network-get-property-names neuron-id)))
+    (unwind-protect
+         (let ((string (cffi:foreign-string-to-lisp p)))
+           (and string
+                (jonathan:parse string)))
+      (%json_string_free p))))
+
+(cffi:defcfun ("Network_set_integration" network-set-integration) :bool
+  (pop_id :int)
+  (method :string))
+
+(cffi:defcfun ("Network_last_error" %network-last-error) :pointer)
+
+(defun network-last-error ()
+  (let ((p (%network-last-error)))
+    (unwind-protect
+         (cffi:foreign-string-to-lisp p)
+      (%json_string_free p))))
+
 (cffi:defcfun ("Network_get_conn_info_by_id" %network-get-conn-info-by-id) :pointer
   (conn-id :int))
 
@@ -176,6 +290,45 @@
 (cffi:defcfun ("Network_run" network-run) :boolean
   (time :double))
 
+(cffi:defcfun ("Network_run_until" %network-run-until) :double
+  (max_time :double)
+  (condition :string))
+
+;;; condition-plist is for instance
+;;; (:|condition| "spike_count" :|neurons| (3 4) :|count| 5), the result
+;;; the time the condition was met or nil
+(defun network-run-until (max-time condition-plist)
+  (let ((time (%network-run-until max-time (jonathan:to-json condition-plist))))
+    (and (>= time 0) time)))
+
+;;; callbacks are defined with cffi:defcallback taking (:double :pointer)
+;;; and returning :bool, false stopping the run
+(cffi:defcfun ("Network_run_with_callback" %network-run-with-callback) :double
+  (time :double)
+  (interval :double)
+  (callback :pointer)
+  (user_data :pointer))
+
+(defun network-run-with-callback (time interval callback &optional (user-data (cffi:null-pointer)))
+  (%network-run-with-callback time interval callback user-data))
+
+(cffi:defcfun ("Network_add_step_hook" %network-add-step-hook) :long
+  (trigger :string)
+  (callback :pointer)
+  (user_data :pointer))
+
+;;; trigger-plist is (:|every| 10) or (:|spikes| (3 4))
+(defun network-add-step-hook (trigger-plist callback &optional (user-data (cffi:null-pointer)))
+  (%network-add-step-hook (jonathan:to-json trigger-plist) callback user-data))
+
+(cffi:defcfun ("Network_remove_step_hook" network-remove-step-hook) :bool
+  (id :int))
+
+(cffi:defcfun ("Network_clear_step_hooks" network-clear-step-hooks) :void)
+
+(cffi:defcfun ("Network_set_precise_timing" network-set-precise-timing) :bool
+  (enabled :bool))
+
 (cffi:defcfun ("Network_get_population_by_id" %network-get-population-by-id) :pointer
   (population_id :int))
 
@@ -187,6 +340,132 @@
                 (list :|population| (jonathan:parse string))))
       (%json_string_free p))))
 
+(cffi:defcfun ("Network_slice_population" %network-slice-population) :pointer
+  (population_id :int)
+  (start :int)
+  (end :int))
+
+(defun network-slice-population (population-id start end)
+  (let ((p (%network-slice-population population-id start end)))
+    (unwind-protect
+         (let ((string (cffi:foreign-string-to-lisp p)))
+           (and string
+                (list :|population| (jonathan:parse string))))
+      (%json_string_free p))))
+
+(cffi:defcfun ("Network_select_population" %network-select-population) :pointer
+  (population_id :int)
+  (indices :string))
+
+(defun network-select-population (population-id indices)
+  (let ((p (%network-select-population population-id (jonathan:to-json indices))))
+    (unwind-protect
+         (let ((string (cffi:foreign-string-to-lisp p)))
+           (and string
+                (list :|population| (jonathan:parse string))))
+      (%json_string_free p))))
+
+(cffi:defcfun ("Network_assemble" %network-assemble) :pointer
+  (population_ids :string))
+
+(defun network-assemble (population-ids)
+  (let ((p (%network-assemble (jonathan:to-json population-ids))))
+    (unwind-protect
+         (let ((string (cffi:foreign-string-to-lisp p)))
+           (and string
+                (list :|population| (jonathan:parse string))))
+      (%json_string_free p))))
+
 (cffi:defcfun ("Network_set_static_poisson_freq" network-set-static-poisson-freq) :boolean
   (neuron_id :int)
   (freq :double))
+
+(cffi:defcfun ("Network_normalize_incoming_weights" %network-normalize-incoming-weights) :bool
+  (conn_ids :string)
+  (target_sum :double))
+
+(defun network-normalize-incoming-weights (conn-ids target-sum)
+  (%network-normalize-incoming-weights (jonathan:to-json conn-ids) target-sum))
+
+(cffi:defcfun ("Network_normalize_population_inputs" network-normalize-population-inputs) :bool
+  (pop_id :int)
+  (target_sum :double))
+
+(cffi:defcfun ("Network_add_weight_normalization" %network-add-weight-normalization) :bool
+  (normalization :string))
+
+;; normalization is a plist such as (:|connections| (0 1 2) :|target_sum| 78 :|interval| 350)
+(defun network-add-weight-normalization (normalization)
+  (%network-add-weight-normalization (jonathan:to-json normalization)))
+
+(cffi:defcfun ("Network_clear_weight_normalizations" network-clear-weight-normalizations) :void)
+
+(cffi:defcfun ("Network_add_threshold_adaptation" %network-add-threshold-adaptation) :bool
+  (pop_id :int)
+  (adaptation :string))
+
+;; adaptation is a plist such as (:|theta_plus| 0.05 :|tau_theta| 1e7)
+(defun network-add-threshold-adaptation (pop-id adaptation)
+  (%network-add-threshold-adaptation pop-id (jonathan:to-json adaptation)))
+
+(cffi:defcfun ("Network_add_synaptic_scaling" %network-add-synaptic-scaling) :bool
+  (scaling :string))
+
+;; scaling is a plist such as (:|connections| (0 1 2) :|target_rate| 5 :|interval| 1000)
+(defun network-add-synaptic-scaling (scaling)
+  (%network-add-synaptic-scaling (jonathan:to-json scaling)))
+
+(cffi:defcfun ("Network_clear_synaptic_scalings" network-clear-synaptic-scalings) :void)
+
+(cffi:defcfun ("Network_set_plasticity" network-set-plasticity) :void
+  (enabled :bool))
+
+(cffi:defcfun ("Network_set_projection_plasticity" %network-set-projection-plasticity) :bool
+  (conn_ids :string)
+  (enabled :bool))
+
+(defun network-set-projection-plasticity (conn-ids enabled)
+  (%network-set-projection-plasticity (jonathan:to-json conn-ids) enabled))
+
+(cffi:defcfun ("Network_reset_state" %network-reset-state) :void
+  (reset_thresholds :bool)
+  (rewind_clock :bool))
+
+(defun network-reset-state (&key reset-thresholds rewind-clock)
+  (%network-reset-state reset-thresholds rewind-clock))
+
+(cffi:defcfun ("Network_set_engine" network-set-engine) :bool
+  (engine :string))
+
+(cffi:defcfun ("Network_set_rates" %network-set-rates) :bool
+  (pop_id :int)
+  (rates :string))
+
+(defun network-set-rates (pop-id rates)
+  (%network-set-rates pop-id (jonathan:to-json rates)))
+
+(cffi:defcfun ("Network_set_rate_schedule" %network-set-rate-schedule) :bool
+  (pop_id :int)
+  (schedule :string))
+
+;; schedule is a plist such as (:|schedule| "sinusoid" :|mean| 20 :|amplitude| 10 :|frequency| 5)
+(defun network-set-rate-schedule (pop-id schedule)
+  (%network-set-rate-schedule pop-id (jonathan:to-json schedule)))
+
+(cffi:defcfun ("Network_encode" %network-encode) :bool
+  (pop_id :int)
+  (coding :string)
+  (values :string)
+  (duration :double))
+
+;; coding is a plist such as (:|coding| "latency" :|threshold| 0 :|max| 255)
+(defun network-encode (pop-id coding values duration)
+  (%network-encode pop-id (jonathan:to-json coding) (jonathan:to-json values) duration))
+
+(cffi:defcfun ("Network_connect_current" %network-connect-current) :bool
+  (pop_id :int)
+  (source :string))
+
+;; source is a plist such as (:|type| "dc" :|amplitude| 100 :|start| 50 :|stop| 150)
+(defun network-connect-current (pop-id source)
+  (%network-connect-current pop-id (jonathan:to-json source)))
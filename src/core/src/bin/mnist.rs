@@ -0,0 +1,131 @@
+// Copyright (c) 2019 Libgirl
+//
+// Released under Apache 2.0 license as described in the file LICENSE.txt.
+
+// Trains the network of Diehl & Cook (2015) on MNIST, as the Lisp example in
+// examples/wheatnnleek-mnist does.
+//
+// usage: mnist <data directory> [samples] [neurons]
+use std::env;
+use std::process;
+use std::time::Instant;
+
+use wheatnnleek::connections::{static_connection, stdp_connection, PostSynapticEffect};
+use wheatnnleek::connectors::{all_to_all, all_to_all_except_diagonal, linear};
+use wheatnnleek::datasets::{Dataset, Presentation};
+use wheatnnleek::models::NeuronType;
+use wheatnnleek::network::Network;
+use wheatnnleek::readout::{label_neurons, vote, ConfusionMatrix, Voting};
+use wheatnnleek::Parameters;
+
+fn parameters(values: &[(&str, f64)]) -> Parameters {
+    values.iter().map(|&(k, v)| (k.to_string(), v)).collect()
+}
+
+fn train(data: &str, samples: usize, neurons: usize) -> Result<(), String> {
+    let dataset = Dataset::read(
+        &format!("{}/train-images.idx3-ubyte", data),
+        &format!("{}/train-labels.idx1-ubyte", data),
+        10,
+    )?;
+
+    let mut network = Network::new();
+    let input = network.create_group(
+        dataset.samples.sample_size(),
+        "StaticPoisson",
+        &Parameters::new(),
+    )?;
+    let excitatory = network.create(
+        neurons,
+        NeuronType::ConductionBasedAdaptiveThresholdLIF,
+        &Parameters::new(),
+    )?;
+    let inhibitory = network.create(
+        neurons,
+        NeuronType::ConductionBasedAdaptiveThresholdLIF,
+        &parameters(&[
+            ("e_i", -85.),
+            ("tau_m", 10.),
+            ("theta", 0.),
+            ("theta_plus", 0.),
+            ("v_th", -40.),
+            ("refact", 2.),
+            ("e_l", -60.),
+            ("reset_v", -45.),
+        ]),
+    )?;
+    let learning = network.connect(
+        &input,
+        &excitatory,
+        &all_to_all::Connector::default(),
+        &stdp_connection::Connection::new(
+            &parameters(&[("weight", -1.), ("delay", 10.)]),
+            PostSynapticEffect::Excitatory,
+        ),
+    );
+    network.connect(
+        &excitatory,
+        &inhibitory,
+        &linear::Connector::default(),
+        &static_connection::Connection::new(
+            &parameters(&[("weight", 5.)]),
+            PostSynapticEffect::Excitatory,
+        ),
+    );
+    network.connect(
+        &inhibitory,
+        &excitatory,
+        &all_to_all_except_diagonal::Connector::default(),
+        &static_connection::Connection::new(&Parameters::new(), PostSynapticEffect::Inhibitory),
+    );
+    network.record_spikes(&excitatory)?;
+
+    let presentation = Presentation::default();
+    let samples = samples.min(dataset.len());
+    let mut responses = Vec::new();
+    let start = Instant::now();
+    for i in 0..samples {
+        // thresholds adapt during presentations only
+        network.set_property(&excitatory, "fix_theta".to_string(), 0.)?;
+        let response =
+            presentation.present(&mut network, &input, &excitatory, dataset.samples.sample(i))?;
+        network.set_property(&excitatory, "fix_theta".to_string(), 1.)?;
+        network.normalize_incoming_weights(&learning, 78.)?;
+        presentation.rest(&mut network, &input)?;
+        println!(
+            "{}/{} label {}: {} spikes in {} attempts ({:.1}s)",
+            i + 1,
+            samples,
+            dataset.labels[i],
+            response.total(),
+            response.attempts,
+            start.elapsed().as_secs_f64()
+        );
+        responses.push(response.counts);
+    }
+
+    // labels learned on the training responses, so the accuracy is optimistic
+    let labels = &dataset.labels[..samples];
+    let neuron_labels = label_neurons(&responses, labels, dataset.classes())?;
+    let predicted = responses
+        .iter()
+        .map(|counts| vote(counts, &neuron_labels, dataset.classes(), Voting::Average))
+        .collect::<Result<Vec<_>, _>>()?;
+    let matrix = ConfusionMatrix::from_predictions(labels, &predicted, dataset.classes());
+    println!("training accuracy {:.3}", matrix.accuracy());
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 {
+        eprintln!("usage: {} <data directory> [samples] [neurons]", args[0]);
+        process::exit(2);
+    }
+    let samples = args.get(2).and_then(|s| s.parse().ok()).unwrap_or(100);
+    let neurons = args.get(3).and_then(|s| s.parse().ok()).unwrap_or(100);
+    if let Err(e) = train(&args[1], samples, neurons) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
@@ -27,8 +27,7 @@ fn main() {
     let variables: Vec<String> = vec!["v_m".to_string(), "spike".to_string()];
     n1.record(variables).unwrap();
 
-    let population_id = n2.get_id();
-    network.record_spikes(population_id).unwrap();
+    network.record_spikes(&n2).unwrap();
     network.connect(
         &n1,
         &n2,
@@ -0,0 +1,38 @@
+// Copyright (c) 2019 Libgirl
+//
+// Released under Apache 2.0 license as described in the file LICENSE.txt.
+
+// Data description of a projection, resolved through the connector and
+// synapse registries, e.g.
+// {"connector": {"type": "linear"},
+//  "synapse": {"type": "static", "params": {"weight": 0.5, "delay": 1}}}
+use crate::connections::PostSynapticEffect;
+use crate::connectors::registry::ConnectorArgs;
+use crate::{Double, Parameters};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConnectorSpec {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(flatten)]
+    pub args: ConnectorArgs,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SynapseSpec {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub params: Parameters,
+    #[serde(default)]
+    pub post_syn_effect: Option<PostSynapticEffect>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConnectionSpec {
+    pub connector: ConnectorSpec,
+    pub synapse: SynapseSpec,
+    // initial weight of each created connection, in creation order
+    #[serde(default)]
+    pub weights: Option<Vec<Double>>,
+}
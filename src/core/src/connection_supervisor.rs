@@ -12,6 +12,8 @@ pub struct ConnectionSupervisor {
     connections_: Vec<Box<Connection>>,
     post_connections_: HashMap<usize, Vec<Num>>,
     pre_connections_: HashMap<usize, Vec<Num>>,
+    plasticity_: bool,
+    precise_timing_: bool,
 }
 
 impl ConnectionSupervisor {
@@ -21,6 +23,8 @@ impl ConnectionSupervisor {
             connections_: Vec::new(),
             post_connections_: HashMap::new(),
             pre_connections_: HashMap::new(),
+            plasticity_: true,
+            precise_timing_: false,
         }
     }
 
@@ -29,6 +33,25 @@ impl ConnectionSupervisor {
         self.connections_.clear();
         self.post_connections_.clear();
         self.pre_connections_.clear();
+        self.plasticity_ = true;
+    }
+
+    // Removes the connections from `len` on, the last ones made.
+    pub fn truncate(&mut self, len: Num) {
+        if len >= self.connections_.len() {
+            return;
+        }
+        self.connections_.truncate(len);
+        self.next_conn_id = len;
+        for ids in self
+            .post_connections_
+            .values_mut()
+            .chain(self.pre_connections_.values_mut())
+        {
+            ids.retain(|&id| id < len);
+        }
+        self.post_connections_.retain(|_, ids| !ids.is_empty());
+        self.pre_connections_.retain(|_, ids| !ids.is_empty());
     }
 
     pub fn add_connection(&mut self, source_id: Index, target_id: Index, syn: &Connection) -> Num {
@@ -43,6 +66,10 @@ impl ConnectionSupervisor {
         conn.set_weight(w);
         conn.set_source(source_id);
         conn.set_target(target_id);
+        if !self.plasticity_ {
+            conn.set_plasticity(false);
+        }
+        conn.set_precise_timing(self.precise_timing_);
         self.next_conn_id = conn_id + 1;
 
         conn.set_id(conn_id);
@@ -113,4 +140,68 @@ impl ConnectionSupervisor {
     pub fn set_weight_by_conn_id(&mut self, conn_id: Num, weight: Double) {
         self.connections_[conn_id].set_weight(weight);
     }
+
+    // Switches the plasticity of all connections, including those created
+    // afterwards.
+    pub fn set_plasticity(&mut self, enabled: bool) {
+        self.plasticity_ = enabled;
+        for conn in self.connections_.iter_mut() {
+            conn.set_plasticity(enabled);
+        }
+    }
+
+    // Applies to the connections created afterwards as well.
+    pub fn set_precise_timing(&mut self, enabled: bool) {
+        self.precise_timing_ = enabled;
+        for conn in self.connections_.iter_mut() {
+            conn.set_precise_timing(enabled);
+        }
+    }
+
+    pub fn reset_state(&mut self) {
+        for conn in self.connections_.iter_mut() {
+            conn.reset_state();
+        }
+    }
+
+    pub fn set_plasticity_by_conn_id(&mut self, conn_id: Num, enabled: bool) {
+        self.connections_[conn_id].set_plasticity(enabled);
+    }
+
+    pub fn plasticity_by_conn_id(&self, conn_id: Num) -> bool {
+        self.connections_[conn_id].plasticity()
+    }
+
+    pub fn len(&self) -> usize {
+        self.connections_.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.connections_.is_empty()
+    }
+
+    pub fn get_incoming_connections(&self, target_id: Index) -> Vec<Num> {
+        match self.pre_connections_.get(&(target_id as usize)) {
+            Some(sources) => sources.to_vec(),
+            _ => Vec::new(),
+        }
+    }
+
+    // Scales the weights of the given connections so that they sum to
+    // `target_sum` for each target. Targets whose weights sum to zero are
+    // left alone.
+    pub fn normalize_incoming(&mut self, conn_ids: &[Num], target_sum: Double) {
+        let mut sums: HashMap<Index, Double> = HashMap::new();
+        for &id in conn_ids {
+            let conn = &self.connections_[id];
+            *sums.entry(conn.target()).or_insert(0.) += conn.weight();
+        }
+        for &id in conn_ids {
+            let sum = sums[&self.connections_[id].target()];
+            if sum > 0. {
+                let w = self.connections_[id].weight();
+                self.connections_[id].set_weight(w * target_sum / sum);
+            }
+        }
+    }
 }
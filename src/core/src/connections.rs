@@ -5,7 +5,7 @@
 use crate::{Double, Index, Num};
 use std::fmt::Debug;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum PostSynapticEffect {
     Excitatory,
     Inhibitory,
@@ -29,6 +29,20 @@ pub trait Connection: ConnectionClone + Debug + std::marker::Send {
 
     fn on_pre_spike(&mut self, t: Double);
     fn on_post_spike(&mut self, t: Double);
+
+    // Plastic connections stop changing their weight while disabled; static
+    // connections ignore the switch.
+    fn set_plasticity(&mut self, _enabled: bool) {}
+    fn plasticity(&self) -> bool {
+        false
+    }
+
+    // Plastic connections whose rule depends on spike times follow the
+    // network's precise timing switch.
+    fn set_precise_timing(&mut self, _enabled: bool) {}
+
+    // Clears the traces of plastic connections, keeping the weight.
+    fn reset_state(&mut self) {}
 }
 
 pub trait ConnectionClone {
@@ -55,5 +69,6 @@ pub struct ConnectionInfo {
     pub delay: Double,
 }
 
+pub mod registry;
 pub mod static_connection;
 pub mod stdp_connection;
@@ -0,0 +1,77 @@
+// Copyright (c) 2019 Libgirl
+//
+// Released under Apache 2.0 license as described in the file LICENSE.txt.
+
+// Synapse types available by name for connections described as data.
+use crate::connections::{static_connection, stdp_connection, Connection, PostSynapticEffect};
+use crate::models::check_params;
+use crate::Parameters;
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+pub type SynapseFactory = fn(&Parameters, PostSynapticEffect) -> Box<dyn Connection>;
+
+#[derive(Clone)]
+struct Entry {
+    factory: SynapseFactory,
+    parameters: Parameters,
+}
+
+lazy_static! {
+    static ref SYNAPSES: RwLock<HashMap<String, Entry>> = RwLock::new(builtin_synapses());
+}
+
+fn builtin_synapses() -> HashMap<String, Entry> {
+    let mut synapses = HashMap::new();
+    synapses.insert(
+        "static".to_string(),
+        Entry {
+            factory: |p, effect| Box::new(static_connection::Connection::new(p, effect)),
+            parameters: static_connection::Connection::parameters(),
+        },
+    );
+    synapses.insert(
+        "stdp".to_string(),
+        Entry {
+            factory: |p, effect| Box::new(stdp_connection::Connection::new(p, effect)),
+            parameters: stdp_connection::Connection::parameters(),
+        },
+    );
+    synapses
+}
+
+// `parameters` lists every key the factory accepts with its default value.
+pub fn register(name: &str, factory: SynapseFactory, parameters: Parameters) -> Result<(), String> {
+    let mut synapses = SYNAPSES.write().unwrap();
+    if synapses.contains_key(name) {
+        return Err(format!("synapse {} is already registered", name));
+    }
+    synapses.insert(
+        name.to_string(),
+        Entry {
+            factory,
+            parameters,
+        },
+    );
+    Ok(())
+}
+
+pub fn names() -> Vec<String> {
+    let mut names: Vec<String> = SYNAPSES.read().unwrap().keys().cloned().collect();
+    names.sort();
+    names
+}
+
+pub fn build(
+    name: &str,
+    params: &Parameters,
+    effect: PostSynapticEffect,
+) -> Result<Box<dyn Connection>, String> {
+    let entry = match SYNAPSES.read().unwrap().get(name) {
+        Some(entry) => entry.clone(),
+        None => return Err(format!("unknown synapse {}", name)),
+    };
+    check_params(params, &entry.parameters)?;
+    Ok((entry.factory)(params, effect))
+}
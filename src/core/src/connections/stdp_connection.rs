@@ -27,6 +27,8 @@ pub struct Connection {
     post_rate_: Double,
     last_decay_t: Double,
     post_syn_effect_: PostSynapticEffect,
+    plastic_: bool,
+    precise_timing_: bool,
 }
 
 impl Connection {
@@ -66,10 +68,16 @@ impl Connection {
             post_rate_: 0.01,
             last_decay_t: -1.,
             post_syn_effect_: post_syn_effect,
+            plastic_: true,
+            precise_timing_: false,
         }
     }
 
     fn decay(&mut self, t: Time) {
+        if self.precise_timing_ {
+            self.decay_exactly(t);
+            return;
+        }
         let dt = Network::resolution();
         let last_decay_t = self.last_decay_t;
 
@@ -102,6 +110,19 @@ impl Connection {
         }
         self.last_decay_t = t;
     }
+
+    // With precise timing, spikes fall between steps, so the traces decay
+    // exactly over the time since the last spike.
+    fn decay_exactly(&mut self, t: Time) {
+        let last_decay_t = self.last_decay_t;
+        if last_decay_t >= 0. && t > last_decay_t {
+            let elapsed = t - last_decay_t;
+            self.a_pre_ *= (-elapsed / self.tc_pre_).exp();
+            self.a_post1_ *= (-elapsed / self.tc_post_1_).exp();
+            self.a_post2_ *= (-elapsed / self.tc_post_2_).exp();
+        }
+        self.last_decay_t = t;
+    }
 }
 
 impl Default for Connection {
@@ -153,6 +174,9 @@ impl CommonConnection for Connection {
     }
 
     fn on_pre_spike(&mut self, t: Double) {
+        if !self.plastic_ {
+            return;
+        }
         self.decay(t);
 
         self.a_pre_ = 1.;
@@ -160,6 +184,9 @@ impl CommonConnection for Connection {
     }
 
     fn on_post_spike(&mut self, t: Double) {
+        if !self.plastic_ {
+            return;
+        }
         self.decay(t);
         let a_post2 = self.a_post2_;
 
@@ -171,4 +198,25 @@ impl CommonConnection for Connection {
         self.a_post1_ = 1.;
         self.a_post2_ = 1.;
     }
+
+    // While frozen the traces are left as they are and decay over the
+    // frozen period once learning resumes.
+    fn set_plasticity(&mut self, enabled: bool) {
+        self.plastic_ = enabled;
+    }
+
+    fn plasticity(&self) -> bool {
+        self.plastic_
+    }
+
+    fn set_precise_timing(&mut self, enabled: bool) {
+        self.precise_timing_ = enabled;
+    }
+
+    fn reset_state(&mut self) {
+        self.a_pre_ = 0.;
+        self.a_post1_ = 0.;
+        self.a_post2_ = 0.;
+        self.last_decay_t = -1.;
+    }
 }
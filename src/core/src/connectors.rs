@@ -15,9 +15,16 @@ pub trait Connector {
         syn: &Connection,
         connection_supervisor: &mut ConnectionSupervisor,
     ) -> Vec<Num>;
+
+    // Fails when the connector does not apply to the populations, checked
+    // before connecting them from a specification.
+    fn check(&self, _pre: &Population, _post: &Population) -> Result<(), String> {
+        Ok(())
+    }
 }
 
 pub mod all_to_all;
 pub mod all_to_all_except_diagonal;
 pub mod array;
 pub mod linear;
+pub mod registry;
@@ -45,4 +45,16 @@ impl CommonConnector for Connector {
         }
         v
     }
+
+    fn check(&self, pre: &Population, post: &Population) -> Result<(), String> {
+        if self.connections_.len() != pre.size() * post.size() {
+            return Err(format!(
+                "connector array has {} entries for {}x{} neurons",
+                self.connections_.len(),
+                pre.size(),
+                post.size()
+            ));
+        }
+        Ok(())
+    }
 }
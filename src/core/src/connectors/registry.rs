@@ -0,0 +1,79 @@
+// Copyright (c) 2019 Libgirl
+//
+// Released under Apache 2.0 license as described in the file LICENSE.txt.
+
+// Connectors available by name, so that connection rules can be described
+// as data, e.g. {"type": "array", "array": "0110"}.
+use crate::connectors::{all_to_all, all_to_all_except_diagonal, array, linear, Connector};
+use lazy_static::lazy_static;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+pub type ConnectorArgs = Map<String, Value>;
+pub type ConnectorFactory = fn(&ConnectorArgs) -> Result<Box<dyn Connector>, String>;
+
+lazy_static! {
+    static ref CONNECTORS: RwLock<HashMap<String, ConnectorFactory>> =
+        RwLock::new(builtin_connectors());
+}
+
+fn no_args(name: &str, args: &ConnectorArgs) -> Result<(), String> {
+    match args.keys().next() {
+        Some(key) => Err(format!("unknown argument {} for connector {}", key, name)),
+        None => Ok(()),
+    }
+}
+
+fn builtin_connectors() -> HashMap<String, ConnectorFactory> {
+    let mut connectors: HashMap<String, ConnectorFactory> = HashMap::new();
+    connectors.insert("all_to_all".to_string(), |args| {
+        no_args("all_to_all", args)?;
+        Ok(Box::new(all_to_all::Connector::default()))
+    });
+    connectors.insert("all_to_all_except_diagonal".to_string(), |args| {
+        no_args("all_to_all_except_diagonal", args)?;
+        Ok(Box::new(all_to_all_except_diagonal::Connector::default()))
+    });
+    connectors.insert("linear".to_string(), |args| {
+        no_args("linear", args)?;
+        Ok(Box::new(linear::Connector::default()))
+    });
+    connectors.insert("array".to_string(), |args| {
+        for key in args.keys() {
+            if key != "array" {
+                return Err(format!("unknown argument {} for connector array", key));
+            }
+        }
+        match args.get("array").and_then(|a| a.as_str()) {
+            Some(a) if a.bytes().all(|b| b == b'0' || b == b'1') => {
+                Ok(Box::new(array::Connector::new(a.as_bytes())))
+            }
+            _ => Err("connector array needs an \"array\" string of 0 and 1".to_string()),
+        }
+    });
+    connectors
+}
+
+pub fn register(name: &str, factory: ConnectorFactory) -> Result<(), String> {
+    let mut connectors = CONNECTORS.write().unwrap();
+    if connectors.contains_key(name) {
+        return Err(format!("connector {} is already registered", name));
+    }
+    connectors.insert(name.to_string(), factory);
+    Ok(())
+}
+
+pub fn names() -> Vec<String> {
+    let mut names: Vec<String> = CONNECTORS.read().unwrap().keys().cloned().collect();
+    names.sort();
+    names
+}
+
+pub fn build(name: &str, args: &ConnectorArgs) -> Result<Box<dyn Connector>, String> {
+    let factory = match CONNECTORS.read().unwrap().get(name) {
+        Some(factory) => *factory,
+        None => return Err(format!("unknown connector {}", name)),
+    };
+    factory(args)
+}
@@ -0,0 +1,266 @@
+// Copyright (c) 2019 Libgirl
+//
+// Released under Apache 2.0 license as described in the file LICENSE.txt.
+
+// Datasets in the IDX format of MNIST, and a harness presenting their
+// samples to a network as the rates of a population of Poisson generators.
+use crate::network::Network;
+use crate::populations::Population;
+use crate::{Double, Time};
+use std::fs;
+
+// An IDX array: `dims[0]` samples of `dims[1] * dims[2] * ...` values each.
+#[derive(Clone, Debug)]
+pub struct Idx {
+    pub dims: Vec<usize>,
+    pub values: Vec<Double>,
+}
+
+impl Idx {
+    pub fn parse(bytes: &[u8]) -> Result<Idx, String> {
+        if bytes.len() < 4 || bytes[0] != 0 || bytes[1] != 0 {
+            return Err("invalid IDX magic number".to_string());
+        }
+        let size: usize = match bytes[2] {
+            0x08 | 0x09 => 1,
+            0x0B => 2,
+            0x0C | 0x0D => 4,
+            0x0E => 8,
+            code => return Err(format!("unknown IDX data type 0x{:02x}", code)),
+        };
+        let header = 4 + 4 * bytes[3] as usize;
+        if bytes.len() < header {
+            return Err("truncated IDX header".to_string());
+        }
+        let dims: Vec<usize> = bytes[4..header]
+            .chunks(4)
+            .map(|d| u32::from_be_bytes([d[0], d[1], d[2], d[3]]) as usize)
+            .collect();
+        // the header may be corrupt, so the sizes are checked for overflow
+        let length = dims
+            .iter()
+            .try_fold(size, |length, &d| length.checked_mul(d))
+            .ok_or_else(|| "IDX dimensions overflow".to_string())?;
+        if bytes.len() - header != length {
+            return Err(format!(
+                "expected {} bytes of IDX data but found {}",
+                length,
+                bytes.len() - header
+            ));
+        }
+        let data = &bytes[header..];
+        let values = match bytes[2] {
+            0x08 => data.iter().map(|&b| b as Double).collect(),
+            0x09 => data.iter().map(|&b| b as i8 as Double).collect(),
+            0x0B => data
+                .chunks(2)
+                .map(|b| i16::from_be_bytes([b[0], b[1]]) as Double)
+                .collect(),
+            0x0C => data
+                .chunks(4)
+                .map(|b| i32::from_be_bytes([b[0], b[1], b[2], b[3]]) as Double)
+                .collect(),
+            0x0D => data
+                .chunks(4)
+                .map(|b| f32::from_be_bytes([b[0], b[1], b[2], b[3]]) as Double)
+                .collect(),
+            _ => data
+                .chunks(8)
+                .map(|b| {
+                    let mut bytes = [0; 8];
+                    bytes.copy_from_slice(b);
+                    f64::from_be_bytes(bytes)
+                })
+                .collect(),
+        };
+        Ok(Idx { dims, values })
+    }
+
+    pub fn read(path: &str) -> Result<Idx, String> {
+        let bytes = fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+        Idx::parse(&bytes).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn len(&self) -> usize {
+        self.dims.first().cloned().unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Number of values of each sample, e.g. 784 for MNIST images.
+    pub fn sample_size(&self) -> usize {
+        self.dims.iter().skip(1).product()
+    }
+
+    pub fn sample(&self, i: usize) -> &[Double] {
+        let size = self.sample_size();
+        &self.values[i * size..(i + 1) * size]
+    }
+}
+
+// Samples with their labels, such as the MNIST training set.
+#[derive(Clone, Debug)]
+pub struct Dataset {
+    pub samples: Idx,
+    pub labels: Vec<usize>,
+    classes: usize,
+}
+
+impl Dataset {
+    // Reads samples and their labels, which must be classes below `classes`.
+    pub fn read(samples_path: &str, labels_path: &str, classes: usize) -> Result<Dataset, String> {
+        let samples = Idx::read(samples_path)?;
+        let labels = Idx::read(labels_path)?;
+        if labels.dims.len() != 1 || labels.len() != samples.len() {
+            return Err(format!(
+                "{}: expected {} labels",
+                labels_path,
+                samples.len()
+            ));
+        }
+        if let Some((i, l)) = labels
+            .values
+            .iter()
+            .enumerate()
+            .find(|(_, &l)| !(l >= 0. && l < classes as Double && l.fract() == 0.))
+        {
+            return Err(format!(
+                "{}: label {} of sample {} is not one of {} classes",
+                labels_path, l, i, classes
+            ));
+        }
+        let labels = labels.values.iter().map(|&l| l as usize).collect();
+        Ok(Dataset {
+            samples,
+            labels,
+            classes,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn classes(&self) -> usize {
+        self.classes
+    }
+}
+
+// How samples are presented, after Diehl & Cook (2015): each value drives a
+// Poisson generator at `value * scale * intensity` Hertz for `duration` ms.
+// When the output fires fewer than `min_spikes` spikes, the sample is
+// presented again with the intensity raised by `intensity_step`, at most
+// `max_retries` times. The network then rests without input so that its
+// state decays before the next sample.
+#[derive(Clone, Debug)]
+pub struct Presentation {
+    pub duration: Time,
+    pub rest: Time,
+    pub scale: Double,
+    pub intensity: Double,
+    pub intensity_step: Double,
+    pub min_spikes: usize,
+    pub max_retries: usize,
+}
+
+impl Default for Presentation {
+    // the MNIST setup: pixels of 0 to 255 drive rates of up to 63.75 Hz
+    fn default() -> Presentation {
+        Presentation {
+            duration: 350.,
+            rest: 150.,
+            scale: 1. / 8.,
+            intensity: 2.,
+            intensity_step: 1.,
+            min_spikes: 5,
+            max_retries: 20,
+        }
+    }
+}
+
+// Output spike counts of a presented sample.
+#[derive(Clone, Debug)]
+pub struct Response {
+    pub counts: Vec<usize>,
+    pub intensity: Double, // of the last presentation
+    pub attempts: usize,
+}
+
+impl Response {
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+}
+
+impl Presentation {
+    // Presents a sample until the output responds enough, without resting.
+    // The output population must be recorded.
+    pub fn present<P: AsRef<Population>, Q: AsRef<Population>>(
+        &self,
+        network: &mut Network,
+        input: &P,
+        output: &Q,
+        sample: &[Double],
+    ) -> Result<Response, String> {
+        let mut intensity = self.intensity;
+        let mut attempts = 0;
+        loop {
+            let rates = sample.iter().map(|v| v * self.scale * intensity).collect();
+            network.set_rates(input, rates)?;
+            network.clear_spike_records(output)?;
+            network.run_for(self.duration);
+            attempts += 1;
+            let counts = network.spike_counts(output);
+            if counts.iter().sum::<usize>() >= self.min_spikes || attempts > self.max_retries {
+                return Ok(Response {
+                    counts,
+                    intensity,
+                    attempts,
+                });
+            }
+            intensity += self.intensity_step;
+        }
+    }
+
+    // Silences the input and lets the network rest.
+    pub fn rest<P: AsRef<Population>>(
+        &self,
+        network: &mut Network,
+        input: &P,
+    ) -> Result<(), String> {
+        network.set_rates(input, vec![0.; input.as_ref().size()])?;
+        network.run_for(self.rest);
+        Ok(())
+    }
+
+    // Presents the first `count` samples of a dataset in order, resting
+    // after each of them, and returns the responses.
+    pub fn present_dataset<P: AsRef<Population>, Q: AsRef<Population>>(
+        &self,
+        network: &mut Network,
+        input: &P,
+        output: &Q,
+        dataset: &Idx,
+        count: usize,
+    ) -> Result<Vec<Response>, String> {
+        if dataset.sample_size() != input.as_ref().size() {
+            return Err(format!(
+                "samples of {} values do not match an input population of {} neurons",
+                dataset.sample_size(),
+                input.as_ref().size()
+            ));
+        }
+        let mut responses = Vec::new();
+        for i in 0..count.min(dataset.len()) {
+            responses.push(self.present(network, input, output, dataset.sample(i))?);
+            self.rest(network, input)?;
+        }
+        Ok(responses)
+    }
+}
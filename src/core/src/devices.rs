@@ -0,0 +1,195 @@
+// Copyright (c) 2019 Libgirl
+//
+// Released under Apache 2.0 license as described in the file LICENSE.txt.
+
+// Stimulation devices injecting current into neurons, in the units of the
+// models' `i_e`. Times are in ms and frequencies in Hertz.
+use crate::{Double, Num, Time};
+use rand::distributions::{Distribution, Normal};
+use std::f64::consts::PI;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Waveform {
+    Dc {
+        amplitude: Double,
+    },
+    // amplitudes[i] from times[i] on, zero before the first time
+    Steps {
+        times: Vec<Time>,
+        amplitudes: Vec<Double>,
+    },
+    // offset + amplitude * sin(2 pi frequency t + phase), phase in radians
+    Ac {
+        amplitude: Double,
+        frequency: Double,
+        #[serde(default)]
+        phase: Double,
+        #[serde(default)]
+        offset: Double,
+    },
+    // drawn independently at every step
+    GaussianNoise {
+        mean: Double,
+        std: Double,
+    },
+    // Ornstein-Uhlenbeck process with stationary mean and standard
+    // deviation, correlated over tau
+    OrnsteinUhlenbeck {
+        mean: Double,
+        std: Double,
+        tau: Time,
+    },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CurrentSource {
+    #[serde(flatten)]
+    pub waveform: Waveform,
+    // the current is only injected from start to stop
+    #[serde(default)]
+    pub start: Time,
+    #[serde(default)]
+    pub stop: Option<Time>,
+}
+
+impl CurrentSource {
+    pub fn new(waveform: Waveform) -> CurrentSource {
+        CurrentSource {
+            waveform,
+            start: 0.,
+            stop: None,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(stop) = self.stop {
+            if stop < self.start {
+                return Err(format!(
+                    "stop time {} is before start time {}",
+                    stop, self.start
+                ));
+            }
+        }
+        match &self.waveform {
+            Waveform::Steps { times, amplitudes } => {
+                if times.len() != amplitudes.len() {
+                    return Err(format!(
+                        "expected {} amplitudes but {} were given",
+                        times.len(),
+                        amplitudes.len()
+                    ));
+                }
+                if times.windows(2).any(|w| w[0] >= w[1]) {
+                    return Err("step times must be strictly increasing".to_string());
+                }
+            }
+            Waveform::GaussianNoise { std, .. } if *std < 0. => {
+                return Err(format!("invalid standard deviation {}", std));
+            }
+            Waveform::OrnsteinUhlenbeck { std, tau, .. } => {
+                if *std < 0. {
+                    return Err(format!("invalid standard deviation {}", std));
+                }
+                if *tau <= 0. {
+                    return Err(format!("invalid time constant {}", tau));
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn is_active(&self, t: Time) -> bool {
+        match self.stop {
+            Some(stop) => t >= self.start && t < stop,
+            None => t >= self.start,
+        }
+    }
+}
+
+// A current source connected to a set of neurons. Noise is drawn
+// independently for each target.
+pub struct CurrentGenerator {
+    source: CurrentSource,
+    targets: Vec<Num>,
+    state: Vec<Double>, // Ornstein-Uhlenbeck process of each target
+}
+
+impl CurrentGenerator {
+    pub fn new(source: CurrentSource, targets: Vec<Num>) -> Result<CurrentGenerator, String> {
+        source.validate()?;
+        let mut generator = CurrentGenerator {
+            state: Vec::new(),
+            source,
+            targets,
+        };
+        generator.reset();
+        Ok(generator)
+    }
+
+    // Restarts noise processes from their mean.
+    pub fn reset(&mut self) {
+        let initial = match self.source.waveform {
+            Waveform::OrnsteinUhlenbeck { mean, .. } => mean,
+            _ => 0.,
+        };
+        self.state = vec![initial; self.targets.len()];
+    }
+
+    pub fn source(&self) -> &CurrentSource {
+        &self.source
+    }
+
+    pub fn targets(&self) -> &[Num] {
+        &self.targets
+    }
+
+    // Adds the current of the step beginning at t to each target.
+    pub fn inject(&mut self, t: Time, dt: Time, currents: &mut [Double]) {
+        if !self.source.is_active(t) {
+            return;
+        }
+        let mut rng = rand::thread_rng();
+        match &self.source.waveform {
+            Waveform::Dc { amplitude } => {
+                for &n in &self.targets {
+                    currents[n] += amplitude;
+                }
+            }
+            Waveform::Steps { times, amplitudes } => {
+                if let Some(i) = times.iter().rposition(|&start| start <= t) {
+                    for &n in &self.targets {
+                        currents[n] += amplitudes[i];
+                    }
+                }
+            }
+            Waveform::Ac {
+                amplitude,
+                frequency,
+                phase,
+                offset,
+            } => {
+                let current = offset + amplitude * (2. * PI * frequency * t / 1000. + phase).sin();
+                for &n in &self.targets {
+                    currents[n] += current;
+                }
+            }
+            Waveform::GaussianNoise { mean, std } => {
+                let normal = Normal::new(*mean, *std);
+                for &n in &self.targets {
+                    currents[n] += normal.sample(&mut rng);
+                }
+            }
+            Waveform::OrnsteinUhlenbeck { mean, std, tau } => {
+                // exact update of the process over one step
+                let decay = (-dt / tau).exp();
+                let normal = Normal::new(0., std * (1. - decay * decay).sqrt());
+                for (x, &n) in self.state.iter_mut().zip(&self.targets) {
+                    *x = mean + (*x - mean) * decay + normal.sample(&mut rng);
+                    currents[n] += *x;
+                }
+            }
+        }
+    }
+}
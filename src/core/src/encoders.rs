@@ -0,0 +1,274 @@
+// Copyright (c) 2019 Libgirl
+//
+// Released under Apache 2.0 license as described in the file LICENSE.txt.
+
+// Encoders turning a vector of values, such as the pixels of an image, into
+// spike trains of an input population during a presentation window. The
+// trains are meant for a population of spike generators, see
+// `Network::encode`.
+use crate::network::Network;
+use crate::{Double, Time};
+use rand::distributions::{Distribution, Exp};
+
+pub trait Encoder {
+    // Number of input neurons needed to encode `size` values.
+    fn neurons(&self, size: usize) -> usize {
+        size
+    }
+
+    // Spike times of each input neuron within [start, start + duration).
+    fn encode(&self, values: &[Double], start: Time, duration: Time) -> Vec<Vec<Time>>;
+}
+
+// Scales a value of [min, max] to [0, 1].
+fn normalize(value: Double, min: Double, max: Double) -> Double {
+    if max > min {
+        ((value - min) / (max - min)).clamp(0., 1.)
+    } else {
+        0.
+    }
+}
+
+// Time at `fraction` of a window, moved to its last step when rounding puts
+// it at the end, which belongs to the next window.
+fn time_in_window(fraction: Double, start: Time, duration: Time) -> Time {
+    let t = start + fraction * duration;
+    if t < start + duration {
+        t
+    } else {
+        (start + duration - Network::resolution()).max(start)
+    }
+}
+
+// Poisson spike trains whose rate grows linearly with the value, from zero at
+// `min` to `max_rate` Hertz at `max`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RateEncoder {
+    pub min: Double,
+    pub max: Double,
+    pub max_rate: Double,
+}
+
+impl RateEncoder {
+    pub fn new(min: Double, max: Double, max_rate: Double) -> RateEncoder {
+        RateEncoder { min, max, max_rate }
+    }
+
+    // Rates in Hertz, for Poisson generators set with `Network::set_rates`.
+    // Non-finite values are silent.
+    pub fn rates(&self, values: &[Double]) -> Vec<Double> {
+        values
+            .iter()
+            .map(|&v| {
+                if v.is_finite() {
+                    normalize(v, self.min, self.max) * self.max_rate
+                } else {
+                    0.
+                }
+            })
+            .collect()
+    }
+}
+
+impl Encoder for RateEncoder {
+    fn encode(&self, values: &[Double], start: Time, duration: Time) -> Vec<Vec<Time>> {
+        let mut rng = rand::thread_rng();
+        self.rates(values)
+            .into_iter()
+            .map(|rate| {
+                let mut train = Vec::new();
+                if rate <= 0. {
+                    return train;
+                }
+                // intervals of a Poisson process are exponentially distributed
+                let intervals = Exp::new(rate / 1000.);
+                let mut t = start + intervals.sample(&mut rng);
+                while t < start + duration {
+                    train.push(t);
+                    t += intervals.sample(&mut rng);
+                }
+                train
+            })
+            .collect()
+    }
+}
+
+// Time-to-first-spike coding: every value above `threshold` fires a single
+// spike, the earlier the larger the value, from the start of the window for
+// `max` and towards its end as the value nears `threshold`. Non-finite
+// values do not fire.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LatencyEncoder {
+    pub threshold: Double,
+    pub max: Double,
+}
+
+impl LatencyEncoder {
+    pub fn new(threshold: Double, max: Double) -> LatencyEncoder {
+        LatencyEncoder { threshold, max }
+    }
+}
+
+impl Encoder for LatencyEncoder {
+    fn encode(&self, values: &[Double], start: Time, duration: Time) -> Vec<Vec<Time>> {
+        values
+            .iter()
+            .map(|&v| {
+                if !v.is_finite() || v <= self.threshold {
+                    Vec::new()
+                } else {
+                    let x = normalize(v, self.threshold, self.max);
+                    vec![time_in_window(1. - x, start, duration)]
+                }
+            })
+            .collect()
+    }
+}
+
+// Rank-order coding: values above `threshold` fire a single spike in
+// decreasing order of value, evenly spread over the window. Equal values
+// fire together and non-finite values do not fire.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RankOrderEncoder {
+    pub threshold: Double,
+}
+
+impl RankOrderEncoder {
+    pub fn new(threshold: Double) -> RankOrderEncoder {
+        RankOrderEncoder { threshold }
+    }
+}
+
+impl Encoder for RankOrderEncoder {
+    fn encode(&self, values: &[Double], start: Time, duration: Time) -> Vec<Vec<Time>> {
+        let mut order: Vec<usize> = (0..values.len())
+            .filter(|&i| values[i].is_finite() && values[i] > self.threshold)
+            .collect();
+        order.sort_by(|&a, &b| values[b].total_cmp(&values[a]));
+        let interval = duration / order.len().max(1) as Double;
+
+        let mut trains = vec![Vec::new(); values.len()];
+        let mut rank = 0;
+        for (k, &i) in order.iter().enumerate() {
+            if k > 0 && values[i] < values[order[k - 1]] {
+                rank = k;
+            }
+            trains[i].push(start + rank as Double * interval);
+        }
+        trains
+    }
+}
+
+// Population coding with Gaussian receptive fields (Bohte et al., 2002):
+// each value is encoded by `fields` neurons whose centers cover [min, max].
+// A neuron fires once, the earlier the closer the value is to its center,
+// unless its response is below `cutoff`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PopulationEncoder {
+    pub fields: usize,
+    pub min: Double,
+    pub max: Double,
+    #[serde(default = "default_beta")]
+    pub beta: Double, // width of the fields relative to their spacing
+    #[serde(default = "default_cutoff")]
+    pub cutoff: Double,
+}
+
+fn default_beta() -> Double {
+    1.5
+}
+
+fn default_cutoff() -> Double {
+    0.1
+}
+
+impl PopulationEncoder {
+    pub fn new(fields: usize, min: Double, max: Double) -> PopulationEncoder {
+        PopulationEncoder {
+            fields,
+            min,
+            max,
+            beta: default_beta(),
+            cutoff: default_cutoff(),
+        }
+    }
+
+    // Responses in [0, 1] of the receptive fields to a value.
+    pub fn responses(&self, value: Double) -> Vec<Double> {
+        let m = self.fields as Double;
+        let range = self.max - self.min;
+        // with fewer than three fields the centers are spread evenly
+        let (spacing, first) = if self.fields > 2 {
+            (range / (m - 2.), self.min - range / (2. * (m - 2.)))
+        } else {
+            (range / m, self.min + range / (2. * m))
+        };
+        let sigma = spacing / self.beta;
+        (0..self.fields)
+            .map(|i| {
+                let center = first + i as Double * spacing;
+                (-(value - center).powi(2) / (2. * sigma * sigma)).exp()
+            })
+            .collect()
+    }
+}
+
+impl Encoder for PopulationEncoder {
+    fn neurons(&self, size: usize) -> usize {
+        size * self.fields
+    }
+
+    fn encode(&self, values: &[Double], start: Time, duration: Time) -> Vec<Vec<Time>> {
+        values
+            .iter()
+            .flat_map(|&v| {
+                // non-finite values do not fire
+                if !v.is_finite() {
+                    return vec![Vec::new(); self.fields];
+                }
+                self.responses(v)
+                    .into_iter()
+                    .map(|r| {
+                        if r.is_nan() || r < self.cutoff {
+                            Vec::new()
+                        } else {
+                            vec![time_in_window(1. - r, start, duration)]
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+// Any of the encoders, as described in JSON, e.g.
+// `{"coding": "latency", "threshold": 0, "max": 255}`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "coding", rename_all = "snake_case")]
+pub enum Coding {
+    Rate(RateEncoder),
+    Latency(LatencyEncoder),
+    RankOrder(RankOrderEncoder),
+    Population(PopulationEncoder),
+}
+
+impl Coding {
+    fn encoder(&self) -> &dyn Encoder {
+        match self {
+            Coding::Rate(encoder) => encoder,
+            Coding::Latency(encoder) => encoder,
+            Coding::RankOrder(encoder) => encoder,
+            Coding::Population(encoder) => encoder,
+        }
+    }
+}
+
+impl Encoder for Coding {
+    fn neurons(&self, size: usize) -> usize {
+        self.encoder().neurons(size)
+    }
+
+    fn encode(&self, values: &[Double], start: Time, duration: Time) -> Vec<Vec<Time>> {
+        self.encoder().encode(values, start, duration)
+    }
+}
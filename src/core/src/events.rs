@@ -16,6 +16,7 @@ pub struct SpikeEvent {
     w_: Double,
     d_: Double,
     m_: Double,
+    o_: Double,
 }
 
 impl SpikeEvent {
@@ -24,6 +25,7 @@ impl SpikeEvent {
             w_: 0.0,
             d_: 1.0,
             m_: 1.0,
+            o_: 0.0,
         }
     }
 
@@ -34,6 +36,16 @@ impl SpikeEvent {
     pub fn set_multiplicity(&mut self, multiplicity: Double) {
         self.m_ = multiplicity;
     }
+
+    // Time of the spike after the beginning of the step it was emitted in.
+    // It is always zero unless precise spike timing is enabled.
+    pub fn offset(&self) -> Double {
+        self.o_
+    }
+
+    pub fn set_offset(&mut self, offset: Double) {
+        self.o_ = offset;
+    }
 }
 
 impl Default for SpikeEvent {
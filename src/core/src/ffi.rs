@@ -3,21 +3,142 @@
 // Released under Apache 2.0 license as described in the file LICENSE.txt.
 extern crate serde_json;
 
-use self::serde_json::Value;
-use crate::models::NeuronType;
-use crate::network::Network;
-use crate::{Num, Parameters, Time};
+use crate::connection_spec::{ConnectionSpec, ConnectorSpec, SynapseSpec};
+use crate::devices::CurrentSource;
+use crate::encoders::Coding;
+use crate::homeostasis::{SynapticScaling, ThresholdAdaptation};
+use crate::hooks::{ConditionWatch, HookTrigger, RunControl, StopCondition};
+use crate::models::inhomogeneous_poisson::RateSchedule;
+use crate::models::{registry, Integration};
+use crate::network::{Engine, Network, ResetOptions, WeightNormalization};
+use crate::parameters::ParameterSpec;
+use crate::populations::{Assembly, Population};
+use crate::{Double, Num, Parameters, Time};
 use lazy_static::lazy_static;
 use std::ffi::{CStr, CString};
-use std::os::raw::c_char;
-use std::str::FromStr;
+use std::mem;
+use std::os::raw::{c_char, c_void};
 use std::sync::{Arc, Mutex};
 
 use crate::connections::{static_connection, stdp_connection, PostSynapticEffect};
-use crate::connectors::{all_to_all, all_to_all_except_diagonal, array, linear};
+use crate::connectors::registry::ConnectorArgs;
+use crate::connectors::{all_to_all, all_to_all_except_diagonal, linear};
 
 lazy_static! {
     static ref NETWORK: Arc<Mutex<Network>> = Arc::new(Mutex::new(Network::new()));
+    static ref LAST_ERROR: Mutex<Option<String>> = Mutex::new(None);
+    static ref PENDING_CALLS: Mutex<Vec<PendingCall>> = Mutex::new(Vec::new());
+}
+
+// Hooks of the FFI get the time and their user data, and stop the run by
+// returning false.
+pub type StepCallback = extern "C" fn(Time, *mut c_void) -> bool;
+
+#[derive(Clone, Copy)]
+struct UserData(*mut c_void);
+
+// the pointer is only handed back to the caller
+unsafe impl Send for UserData {}
+
+// Hooks of the FFI are called once the network is unlocked, so that they can
+// use the other functions of this interface: the network hook standing for
+// them records the call and pauses the run.
+struct PendingCall {
+    callback: StepCallback,
+    user_data: UserData,
+    time: Time,
+}
+
+fn add_ffi_hook(
+    network: &mut Network,
+    trigger: HookTrigger,
+    callback: StepCallback,
+    user_data: UserData,
+) -> Result<Num, String> {
+    network.add_step_hook(
+        trigger,
+        Box::new(move |_, time| {
+            PENDING_CALLS.lock().unwrap().push(PendingCall {
+                callback,
+                user_data,
+                time,
+            });
+            RunControl::Stop
+        }),
+    )
+}
+
+// Runs for `t` ms, or until the watched condition is met, calling the hooks
+// of the FFI between parts of the run, and returns the time reached.
+fn run_unlocked(t: Time, mut watch: Option<&mut ConditionWatch>) -> Time {
+    let network = NETWORK.clone();
+    let end = {
+        let mut network = network.lock().unwrap();
+        network.begin_run();
+        network.current_time() + t
+    };
+    let half_step = Network::resolution() / 2.;
+    loop {
+        let reached = {
+            let mut network = network.lock().unwrap();
+            let left = end - network.current_time();
+            match watch {
+                Some(ref mut watch) => network.advance_until(left, watch),
+                None => network.advance(left),
+            }
+        };
+        let calls = mem::take(&mut *PENDING_CALLS.lock().unwrap());
+        // without calls, the run was stopped by another hook
+        let mut stop = calls.is_empty();
+        for call in calls {
+            if !(call.callback)(call.time, call.user_data.0) {
+                stop = true;
+            }
+        }
+        if stop || reached + half_step >= end {
+            break;
+        }
+    }
+    let mut network = network.lock().unwrap();
+    network.end_run();
+    network.current_time()
+}
+
+// Unwraps a result in a function of the FFI, or sets the last error and
+// returns `$fail`.
+macro_rules! try_ffi {
+    ($result:expr, $fail:expr) => {
+        match $result {
+            Ok(value) => value,
+            Err(e) => {
+                set_last_error(e);
+                return $fail;
+            }
+        }
+    };
+}
+
+// Reads a string argument of the FFI, failing on null or non-UTF-8 input.
+fn c_str<'a>(buf: *const c_char) -> Result<&'a str, String> {
+    if buf.is_null() {
+        return Err("unexpected null string".to_string());
+    }
+    unsafe { CStr::from_ptr(buf) }
+        .to_str()
+        .map_err(|e| format!("invalid string: {}", e))
+}
+
+fn set_last_error(message: String) {
+    *LAST_ERROR.lock().unwrap() = Some(message);
+}
+
+// Returns the message of the last failed call, or null if there is none.
+#[no_mangle]
+pub extern "C" fn Network_last_error() -> *mut c_char {
+    match LAST_ERROR.lock().unwrap().take() {
+        Some(message) => CString::new(message).unwrap().into_raw(),
+        None => std::ptr::null_mut(),
+    }
 }
 
 #[no_mangle]
@@ -42,10 +163,11 @@ pub extern "C" fn sum(a: i32, b: i32) -> i32 {
 }
 
 #[no_mangle]
-pub extern "C" fn Network_clear() {
+pub extern "C" fn Network_clear() -> bool {
     let network = NETWORK.clone();
     let mut network = network.lock().unwrap();
-    (*network).clear();
+    try_ffi!((*network).clear(), false);
+    true
 }
 
 #[no_mangle]
@@ -55,44 +177,98 @@ pub extern "C" fn Network_create(
     rests_buf: *const c_char,
 ) -> *mut c_char {
     let network = NETWORK.clone();
-    let neuron_type_str: &CStr = unsafe { CStr::from_ptr(neuron_type_buf) };
-    let neuron_type: NeuronType = neuron_type_str
-        .to_str()
-        .unwrap()
-        .to_owned()
-        .parse()
-        .unwrap();
-    let rests_str: &CStr = unsafe { CStr::from_ptr(rests_buf) };
-    let rests_: String = rests_str.to_str().unwrap().to_owned().parse().unwrap();
-    let rests: serde_json::Map<String, Value> = match rests_.as_ref() {
-        "[]" => serde_json::Map::default(),
-        _ => serde_json::from_str(&rests_).unwrap(),
+    let neuron_type_str = try_ffi!(c_str(neuron_type_buf), std::ptr::null_mut());
+    let neuron_type: String = neuron_type_str.to_owned();
+    let rests_str = try_ffi!(c_str(rests_buf), std::ptr::null_mut());
+    let rests_: String = rests_str.to_owned();
+    // values are numbers, per-neuron arrays or distributions such as
+    // {"distribution": "uniform", "low": 0.02, "high": 0.1}
+    let spec: ParameterSpec = match rests_.as_ref() {
+        "[]" => ParameterSpec::default(),
+        json => match serde_json::from_str(json) {
+            Ok(spec) => spec,
+            Err(e) => {
+                set_last_error(format!("invalid parameters: {}", e));
+                return std::ptr::null_mut();
+            }
+        },
     };
 
-    let mut network_params = Parameters::new();
-
-    for entry in rests.iter() {
-        let key = entry.0;
-        let value: std::string::String = entry.1.to_string();
-        let value = f64::from_str(&value).unwrap();
-        network_params.insert(key.to_string(), value);
+    let mut network = network.lock().unwrap();
+    match (*network).create_heterogeneous(neuron_number, &neuron_type, &spec) {
+        Ok(population) => {
+            let ret = CString::new(serde_json::to_string(&population).unwrap()).unwrap();
+            ret.into_raw()
+        }
+        Err(e) => {
+            set_last_error(e);
+            std::ptr::null_mut()
+        }
     }
+}
+
+#[no_mangle]
+pub extern "C" fn Network_create_group(
+    neuron_number: usize,
+    model_buf: *const c_char,
+    params_buf: *const c_char,
+) -> *mut c_char {
+    let network = NETWORK.clone();
+    let model_str = try_ffi!(c_str(model_buf), std::ptr::null_mut());
+    let params_str = try_ffi!(c_str(params_buf), std::ptr::null_mut());
+    let params: Parameters = match params_str {
+        "[]" => Parameters::new(),
+        json => match serde_json::from_str(json) {
+            Ok(params) => params,
+            Err(e) => {
+                set_last_error(format!("invalid parameters: {}", e));
+                return std::ptr::null_mut();
+            }
+        },
+    };
 
     let mut network = network.lock().unwrap();
-    let population = (*network)
-        .create(neuron_number, neuron_type, &network_params)
-        .unwrap();
-    let ret = CString::new(serde_json::to_string(&population).unwrap()).unwrap();
+    match (*network).create_group(neuron_number, model_str, &params) {
+        Ok(population) => {
+            let ret = CString::new(serde_json::to_string(&population).unwrap()).unwrap();
+            ret.into_raw()
+        }
+        Err(e) => {
+            set_last_error(e);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn Network_neuron_models() -> *mut c_char {
+    let result = registry::names();
+    let ret = CString::new(serde_json::to_string(&result).unwrap()).unwrap();
     ret.into_raw()
 }
 
+#[no_mangle]
+pub extern "C" fn Network_neuron_model_parameters(model_buf: *const c_char) -> *mut c_char {
+    let model_str = try_ffi!(c_str(model_buf), std::ptr::null_mut());
+    match registry::parameters(model_str) {
+        Ok(result) => {
+            let ret = CString::new(serde_json::to_string(&result).unwrap()).unwrap();
+            ret.into_raw()
+        }
+        Err(e) => {
+            set_last_error(e);
+            std::ptr::null_mut()
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn Network_connect(id0: usize, id1: usize) -> *mut c_char {
     let network = NETWORK.clone();
 
     let mut network = network.lock().unwrap();
-    let population1 = (*network).get_population_by_id(id0);
-    let population2 = (*network).get_population_by_id(id1);
+    let population1 = try_ffi!((*network).get_population_by_id(id0), std::ptr::null_mut());
+    let population2 = try_ffi!((*network).get_population_by_id(id1), std::ptr::null_mut());
     let result = (*network).connect(
         &population1,
         &population2,
@@ -115,13 +291,8 @@ pub extern "C" fn Network_static_connect(
     weights_buf: *const c_char,
 ) -> *mut c_char {
     let network = NETWORK.clone();
-    let post_syn_effect_str: &CStr = unsafe { CStr::from_ptr(post_syn_effect_buf) };
-    let post_syn_effect_: String = post_syn_effect_str
-        .to_str()
-        .unwrap()
-        .to_owned()
-        .parse()
-        .unwrap();
+    let post_syn_effect_str = try_ffi!(c_str(post_syn_effect_buf), std::ptr::null_mut());
+    let post_syn_effect_: String = post_syn_effect_str.to_owned();
 
     let post_syn_effect: PostSynapticEffect = match post_syn_effect_.as_ref() {
         "Inhibitory" => PostSynapticEffect::Inhibitory,
@@ -129,15 +300,15 @@ pub extern "C" fn Network_static_connect(
     };
 
     let mut network = network.lock().unwrap();
-    let population1 = (*network).get_population_by_id(id0);
-    let population2 = (*network).get_population_by_id(id1);
+    let population1 = try_ffi!((*network).get_population_by_id(id0), std::ptr::null_mut());
+    let population2 = try_ffi!((*network).get_population_by_id(id1), std::ptr::null_mut());
 
     let mut params = Parameters::new();
     params.insert("weight".to_string(), weight);
     params.insert("delay".to_string(), connection_delay);
 
-    let connector_str: &CStr = unsafe { CStr::from_ptr(connector_buf) };
-    let connector_: String = connector_str.to_str().unwrap().to_owned().parse().unwrap();
+    let connector_str = try_ffi!(c_str(connector_buf), std::ptr::null_mut());
+    let connector_: String = connector_str.to_owned();
     let result = match connector_.as_ref() {
         "linear" => {
             (*network).connect(
@@ -156,19 +327,36 @@ pub extern "C" fn Network_static_connect(
             );
         }
         "array" => {
-            let ar_str: &CStr = unsafe { CStr::from_ptr(array_buf) };
-            let ar: &[u8] = ar_str.to_bytes();
-            let weights_str: &CStr = unsafe { CStr::from_ptr(weights_buf) };
-            let weights: Vec<f64> = serde_json::from_str(weights_str.to_str().unwrap()).unwrap();
-            (*network).connect_with_initial_weights(
-                &population1,
-                &population2,
-                weights,
-                &array::Connector::new(ar),
-                &static_connection::Connection::new(&params, post_syn_effect),
+            let ar_str = try_ffi!(c_str(array_buf), std::ptr::null_mut());
+            let weights_str = try_ffi!(c_str(weights_buf), std::ptr::null_mut());
+            let weights: Vec<f64> = match serde_json::from_str(weights_str) {
+                Ok(weights) => weights,
+                Err(e) => {
+                    set_last_error(format!("invalid weights: {}", e));
+                    return std::ptr::null_mut();
+                }
+            };
+            // checked against the populations before connecting
+            let mut args = ConnectorArgs::new();
+            args.insert("array".to_string(), ar_str.into());
+            let spec = ConnectionSpec {
+                connector: ConnectorSpec {
+                    kind: "array".to_string(),
+                    args,
+                },
+                synapse: SynapseSpec {
+                    kind: "static".to_string(),
+                    params,
+                    post_syn_effect: Some(post_syn_effect),
+                },
+                weights: Some(weights),
+            };
+            try_ffi!(
+                (*network).connect_spec(&population1, &population2, &spec),
+                std::ptr::null_mut()
             );
         }
-        _ => {
+        "all_to_all" => {
             (*network).connect(
                 &population1,
                 &population2,
@@ -176,6 +364,10 @@ pub extern "C" fn Network_static_connect(
                 &static_connection::Connection::new(&params, post_syn_effect),
             );
         }
+        _ => {
+            set_last_error(format!("unknown connector {}", connector_));
+            return std::ptr::null_mut();
+        }
     };
     let ret = CString::new(serde_json::to_string(&result).unwrap()).unwrap();
     ret.into_raw()
@@ -189,8 +381,8 @@ pub extern "C" fn Network_stdp_connect(
 ) -> *mut c_char {
     let network = NETWORK.clone();
     let mut network = network.lock().unwrap();
-    let population1 = (*network).get_population_by_id(id0);
-    let population2 = (*network).get_population_by_id(id1);
+    let population1 = try_ffi!((*network).get_population_by_id(id0), std::ptr::null_mut());
+    let population2 = try_ffi!((*network).get_population_by_id(id1), std::ptr::null_mut());
     let mut params = Parameters::new();
     params.insert("weight".to_string(), -1.);
     params.insert("delay".to_string(), connection_delay);
@@ -204,11 +396,43 @@ pub extern "C" fn Network_stdp_connect(
     ret.into_raw()
 }
 
+// Connects two populations as described by a JSON `ConnectionSpec`.
+#[no_mangle]
+pub extern "C" fn Network_connect_spec(
+    id0: usize,
+    id1: usize,
+    spec_buf: *const c_char,
+) -> *mut c_char {
+    let network = NETWORK.clone();
+    let mut network = network.lock().unwrap();
+    let spec_str = try_ffi!(c_str(spec_buf), std::ptr::null_mut());
+    let spec: ConnectionSpec = match serde_json::from_str(spec_str) {
+        Ok(spec) => spec,
+        Err(e) => {
+            set_last_error(format!("invalid connection spec: {}", e));
+            return std::ptr::null_mut();
+        }
+    };
+    let population1 = try_ffi!((*network).get_population_by_id(id0), std::ptr::null_mut());
+    let population2 = try_ffi!((*network).get_population_by_id(id1), std::ptr::null_mut());
+    match (*network).connect_spec(&population1, &population2, &spec) {
+        Ok(result) => {
+            let ret = CString::new(serde_json::to_string(&result).unwrap()).unwrap();
+            ret.into_raw()
+        }
+        Err(e) => {
+            set_last_error(e);
+            std::ptr::null_mut()
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn Network_record_spikes(population_id: usize) -> bool {
     let network = NETWORK.clone();
     let mut network = network.lock().unwrap();
-    (*network).record_spikes(population_id).unwrap();
+    let population = try_ffi!((*network).get_population_by_id(population_id), false);
+    (*network).record_spikes(&population).unwrap();
     true
 }
 
@@ -216,7 +440,8 @@ pub extern "C" fn Network_record_spikes(population_id: usize) -> bool {
 pub extern "C" fn Network_clear_spike_records(population_id: usize) -> bool {
     let network = NETWORK.clone();
     let mut network = network.lock().unwrap();
-    (*network).clear_spike_records(population_id).unwrap();
+    let population = try_ffi!((*network).get_population_by_id(population_id), false);
+    (*network).clear_spike_records(&population).unwrap();
     true
 }
 
@@ -247,9 +472,139 @@ pub extern "C" fn Network_get_conn_info_by_id(conn_id: Num) -> *mut c_char {
 
 #[no_mangle]
 pub extern "C" fn Network_run(t: Time) -> bool {
+    // as Network::run
+    run_unlocked(t / Network::resolution(), None);
+    true
+}
+
+// Returns the time reached, or a negative value on error.
+#[no_mangle]
+pub extern "C" fn Network_run_with_callback(
+    t: Time,
+    interval: Time,
+    callback: StepCallback,
+    user_data: *mut c_void,
+) -> Time {
+    let id = {
+        let network = NETWORK.clone();
+        let mut network = network.lock().unwrap();
+        let trigger = HookTrigger::Every(interval);
+        match add_ffi_hook(&mut network, trigger, callback, UserData(user_data)) {
+            Ok(id) => id,
+            Err(e) => {
+                set_last_error(e);
+                return -1.;
+            }
+        }
+    };
+    let reached = run_unlocked(t, None);
+    let network = NETWORK.clone();
+    let _ = network.lock().unwrap().remove_step_hook(id);
+    reached
+}
+
+// Returns the time the condition was met, or a negative value when it was not
+// or on error, with the last error set.
+#[no_mangle]
+pub extern "C" fn Network_run_until(max_t: Time, condition_buf: *const c_char) -> Time {
+    let condition_str = try_ffi!(c_str(condition_buf), -1.);
+    let condition: StopCondition = match serde_json::from_str(condition_str) {
+        Ok(condition) => condition,
+        Err(e) => {
+            set_last_error(format!("invalid condition: {}", e));
+            return -1.;
+        }
+    };
+    let watch = {
+        let network = NETWORK.clone();
+        let mut network = network.lock().unwrap();
+        network.watch(&condition)
+    };
+    let mut watch = match watch {
+        Ok(watch) => watch,
+        Err(e) => {
+            set_last_error(e);
+            return -1.;
+        }
+    };
+    run_unlocked(max_t, Some(&mut watch));
+    let network = NETWORK.clone();
+    network.lock().unwrap().unwatch();
+    watch.met.unwrap_or(-1.)
+}
+
+// Returns the id of the hook, or -1 on error.
+#[no_mangle]
+pub extern "C" fn Network_add_step_hook(
+    trigger_buf: *const c_char,
+    callback: StepCallback,
+    user_data: *mut c_void,
+) -> i64 {
+    let network = NETWORK.clone();
+    let mut network = network.lock().unwrap();
+    let trigger_str = try_ffi!(c_str(trigger_buf), -1);
+    let trigger: HookTrigger = match serde_json::from_str(trigger_str) {
+        Ok(trigger) => trigger,
+        Err(e) => {
+            set_last_error(format!("invalid hook trigger: {}", e));
+            return -1;
+        }
+    };
+    match add_ffi_hook(&mut network, trigger, callback, UserData(user_data)) {
+        Ok(id) => id as i64,
+        Err(e) => {
+            set_last_error(e);
+            -1
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn Network_remove_step_hook(id: usize) -> bool {
+    let network = NETWORK.clone();
+    let mut network = network.lock().unwrap();
+    match (*network).remove_step_hook(id) {
+        Ok(()) => true,
+        Err(e) => {
+            set_last_error(e);
+            false
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn Network_clear_step_hooks() {
+    let network = NETWORK.clone();
+    let mut network = network.lock().unwrap();
+    (*network).clear_step_hooks();
+}
+
+#[no_mangle]
+pub extern "C" fn Network_set_precise_timing(enabled: bool) -> bool {
     let network = NETWORK.clone();
     let mut network = network.lock().unwrap();
-    (*network).run(t);
+    match (*network).set_precise_timing(enabled) {
+        Ok(()) => true,
+        Err(e) => {
+            set_last_error(e);
+            false
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn Network_set_engine(engine_buf: *const c_char) -> bool {
+    let network = NETWORK.clone();
+    let mut network = network.lock().unwrap();
+    let engine_str = try_ffi!(c_str(engine_buf), false);
+    let engine: Engine = match engine_str.parse() {
+        Ok(engine) => engine,
+        Err(_) => {
+            set_last_error(format!("unknown engine {}", engine_str));
+            return false;
+        }
+    };
+    (*network).set_engine(engine);
     true
 }
 
@@ -257,28 +612,123 @@ pub extern "C" fn Network_run(t: Time) -> bool {
 pub extern "C" fn Network_get_population_by_id(population_id: usize) -> *mut c_char {
     let network = NETWORK.clone();
     let network = network.lock().unwrap();
-    let population = (*network).get_population_by_id(population_id);
+    let population = try_ffi!(
+        (*network).get_population_by_id(population_id),
+        std::ptr::null_mut()
+    );
     let ret = CString::new(serde_json::to_string(&population).unwrap()).unwrap();
     ret.into_raw()
 }
 
+#[no_mangle]
+pub extern "C" fn Network_slice_population(
+    population_id: usize,
+    start: usize,
+    end: usize,
+) -> *mut c_char {
+    let network = NETWORK.clone();
+    let mut network = network.lock().unwrap();
+    let population = try_ffi!(
+        (*network).get_population_by_id(population_id),
+        std::ptr::null_mut()
+    );
+    let view = match population.slice(start..end) {
+        Ok(view) => view,
+        Err(e) => {
+            set_last_error(e);
+            return std::ptr::null_mut();
+        }
+    };
+    let result = (*network).add_population(&view);
+    let ret = CString::new(serde_json::to_string(&result).unwrap()).unwrap();
+    ret.into_raw()
+}
+
+#[no_mangle]
+pub extern "C" fn Network_select_population(
+    population_id: usize,
+    indices_buf: *const c_char,
+) -> *mut c_char {
+    let network = NETWORK.clone();
+    let mut network = network.lock().unwrap();
+    let indices_str = try_ffi!(c_str(indices_buf), std::ptr::null_mut());
+    let indices: Vec<usize> = match serde_json::from_str(indices_str) {
+        Ok(indices) => indices,
+        Err(e) => {
+            set_last_error(format!("invalid indices: {}", e));
+            return std::ptr::null_mut();
+        }
+    };
+    let population = try_ffi!(
+        (*network).get_population_by_id(population_id),
+        std::ptr::null_mut()
+    );
+    let view = match population.select(&indices) {
+        Ok(view) => view,
+        Err(e) => {
+            set_last_error(e);
+            return std::ptr::null_mut();
+        }
+    };
+    let result = (*network).add_population(&view);
+    let ret = CString::new(serde_json::to_string(&result).unwrap()).unwrap();
+    ret.into_raw()
+}
+
+#[no_mangle]
+pub extern "C" fn Network_assemble(population_ids_buf: *const c_char) -> *mut c_char {
+    let network = NETWORK.clone();
+    let mut network = network.lock().unwrap();
+    let ids_str = try_ffi!(c_str(population_ids_buf), std::ptr::null_mut());
+    let ids: Vec<usize> = match serde_json::from_str(ids_str) {
+        Ok(ids) => ids,
+        Err(e) => {
+            set_last_error(format!("invalid population ids: {}", e));
+            return std::ptr::null_mut();
+        }
+    };
+    let members: Vec<Box<Population>> = try_ffi!(
+        ids.iter()
+            .map(|id| (*network).get_population_by_id(*id))
+            .collect(),
+        std::ptr::null_mut()
+    );
+    let member_refs: Vec<&Population> = members.iter().map(|m| m.as_ref()).collect();
+    let assembly = Assembly::new(&member_refs);
+    let result = (*network).add_population(&assembly);
+    let ret = CString::new(serde_json::to_string(&result).unwrap()).unwrap();
+    ret.into_raw()
+}
+
 #[no_mangle]
 pub extern "C" fn Network_set_static_poisson_freq(neuron_id: Num, freq: f64) -> bool {
     let network = NETWORK.clone();
     let mut params = Parameters::new();
     params.insert("freq".to_string(), freq);
     let mut network = network.lock().unwrap();
-    (*network).set_neuron_params(neuron_id, &params);
-    true
+    match (*network).set_neuron_params(neuron_id, &params) {
+        Ok(()) => true,
+        Err(e) => {
+            set_last_error(e);
+            false
+        }
+    }
 }
 
 #[no_mangle]
-pub extern "C" fn Network_set_property(pop_id: usize, name_buf: *const c_char, value: f64) {
+pub extern "C" fn Network_set_property(pop_id: usize, name_buf: *const c_char, value: f64) -> bool {
     let network = NETWORK.clone();
     let mut network = network.lock().unwrap();
-    let name_str: &CStr = unsafe { CStr::from_ptr(name_buf) };
-    let name: String = name_str.to_str().unwrap().to_owned().parse().unwrap();
-    (*network).set_property(pop_id, name, value);
+    let name_str = try_ffi!(c_str(name_buf), false);
+    let name: String = name_str.to_owned();
+    let population = try_ffi!((*network).get_population_by_id(pop_id), false);
+    match (*network).set_property(&population, name, value) {
+        Ok(()) => true,
+        Err(e) => {
+            set_last_error(e);
+            false
+        }
+    }
 }
 
 #[no_mangle]
@@ -286,23 +736,388 @@ pub extern "C" fn Network_set_properties(
     pop_id: usize,
     name_buf: *const c_char,
     value_buf: *const c_char,
-) {
+) -> bool {
+    let network = NETWORK.clone();
+    let mut network = network.lock().unwrap();
+    let name_str = try_ffi!(c_str(name_buf), false);
+    let name: String = name_str.to_owned();
+    let value_str = try_ffi!(c_str(value_buf), false);
+    let value: Vec<f64> = match serde_json::from_str(value_str) {
+        Ok(value) => value,
+        Err(e) => {
+            set_last_error(format!("invalid property values: {}", e));
+            return false;
+        }
+    };
+    let population = try_ffi!((*network).get_population_by_id(pop_id), false);
+    match (*network).set_properties(&population, name, value) {
+        Ok(()) => true,
+        Err(e) => {
+            set_last_error(e);
+            false
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn Network_set_spike_times(pop_id: usize, times_buf: *const c_char) -> bool {
+    let network = NETWORK.clone();
+    let mut network = network.lock().unwrap();
+    let times_str = try_ffi!(c_str(times_buf), false);
+    let times: Vec<Vec<Time>> = match serde_json::from_str(times_str) {
+        Ok(times) => times,
+        Err(e) => {
+            set_last_error(format!("invalid spike times: {}", e));
+            return false;
+        }
+    };
+    let population = try_ffi!((*network).get_population_by_id(pop_id), false);
+    match (*network).set_spike_times(&population, &times) {
+        Ok(()) => true,
+        Err(e) => {
+            set_last_error(e);
+            false
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn Network_load_spike_times(pop_id: usize, path_buf: *const c_char) -> bool {
+    let network = NETWORK.clone();
+    let mut network = network.lock().unwrap();
+    let path_str = try_ffi!(c_str(path_buf), false);
+    let population = try_ffi!((*network).get_population_by_id(pop_id), false);
+    match (*network).load_spike_times(&population, path_str) {
+        Ok(()) => true,
+        Err(e) => {
+            set_last_error(e);
+            false
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn Network_connect_current(pop_id: usize, source_buf: *const c_char) -> bool {
     let network = NETWORK.clone();
     let mut network = network.lock().unwrap();
-    let name_str: &CStr = unsafe { CStr::from_ptr(name_buf) };
-    let name: String = name_str.to_str().unwrap().to_owned().parse().unwrap();
-    let value_str: &CStr = unsafe { CStr::from_ptr(value_buf) };
-    let value: Vec<f64> = serde_json::from_str(value_str.to_str().unwrap()).unwrap();
-    (*network).set_properties(pop_id, name, value);
+    let source_str = try_ffi!(c_str(source_buf), false);
+    let source: CurrentSource = match serde_json::from_str(source_str) {
+        Ok(source) => source,
+        Err(e) => {
+            set_last_error(format!("invalid current source: {}", e));
+            return false;
+        }
+    };
+    let population = try_ffi!((*network).get_population_by_id(pop_id), false);
+    match (*network).connect_current(source, &population) {
+        Ok(_) => true,
+        Err(e) => {
+            set_last_error(e);
+            false
+        }
+    }
+}
+
+// Normalizes the incoming weights of a JSON array of connection ids.
+#[no_mangle]
+pub extern "C" fn Network_normalize_incoming_weights(
+    conn_ids_buf: *const c_char,
+    target_sum: f64,
+) -> bool {
+    let network = NETWORK.clone();
+    let mut network = network.lock().unwrap();
+    let conn_ids_str = try_ffi!(c_str(conn_ids_buf), false);
+    let conn_ids: Vec<Num> = match serde_json::from_str(conn_ids_str) {
+        Ok(conn_ids) => conn_ids,
+        Err(e) => {
+            set_last_error(format!("invalid connection ids: {}", e));
+            return false;
+        }
+    };
+    match (*network).normalize_incoming_weights(&conn_ids, target_sum) {
+        Ok(()) => true,
+        Err(e) => {
+            set_last_error(e);
+            false
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn Network_normalize_population_inputs(pop_id: usize, target_sum: f64) -> bool {
+    let network = NETWORK.clone();
+    let mut network = network.lock().unwrap();
+    let population = try_ffi!((*network).get_population_by_id(pop_id), false);
+    match (*network).normalize_population_inputs(&population, target_sum) {
+        Ok(()) => true,
+        Err(e) => {
+            set_last_error(e);
+            false
+        }
+    }
+}
+
+// Schedules a normalization described in JSON, e.g.
+// `{"connections": [0, 1], "target_sum": 78, "interval": 350}`.
+#[no_mangle]
+pub extern "C" fn Network_add_weight_normalization(normalization_buf: *const c_char) -> bool {
+    let network = NETWORK.clone();
+    let mut network = network.lock().unwrap();
+    let normalization_str = try_ffi!(c_str(normalization_buf), false);
+    let normalization: WeightNormalization = match serde_json::from_str(normalization_str) {
+        Ok(normalization) => normalization,
+        Err(e) => {
+            set_last_error(format!("invalid weight normalization: {}", e));
+            return false;
+        }
+    };
+    match (*network).add_weight_normalization(normalization) {
+        Ok(_) => true,
+        Err(e) => {
+            set_last_error(e);
+            false
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn Network_clear_weight_normalizations() {
+    let network = NETWORK.clone();
+    let mut network = network.lock().unwrap();
+    (*network).clear_weight_normalizations();
+}
+
+// Attaches a threshold adaptation described in JSON, e.g.
+// `{"theta_plus": 0.05, "tau_theta": 1e7}`, to the neurons of a population.
+#[no_mangle]
+pub extern "C" fn Network_add_threshold_adaptation(
+    pop_id: usize,
+    adaptation_buf: *const c_char,
+) -> bool {
+    let network = NETWORK.clone();
+    let mut network = network.lock().unwrap();
+    let adaptation_str = try_ffi!(c_str(adaptation_buf), false);
+    let adaptation: ThresholdAdaptation = match serde_json::from_str(adaptation_str) {
+        Ok(adaptation) => adaptation,
+        Err(e) => {
+            set_last_error(format!("invalid threshold adaptation: {}", e));
+            return false;
+        }
+    };
+    let population = try_ffi!((*network).get_population_by_id(pop_id), false);
+    match (*network).add_threshold_adaptation(&population, &adaptation) {
+        Ok(()) => true,
+        Err(e) => {
+            set_last_error(e);
+            false
+        }
+    }
+}
+
+// Schedules a synaptic scaling described in JSON, e.g.
+// `{"connections": [0, 1], "target_rate": 5, "interval": 1000}`.
+#[no_mangle]
+pub extern "C" fn Network_add_synaptic_scaling(scaling_buf: *const c_char) -> bool {
+    let network = NETWORK.clone();
+    let mut network = network.lock().unwrap();
+    let scaling_str = try_ffi!(c_str(scaling_buf), false);
+    let scaling: SynapticScaling = match serde_json::from_str(scaling_str) {
+        Ok(scaling) => scaling,
+        Err(e) => {
+            set_last_error(format!("invalid synaptic scaling: {}", e));
+            return false;
+        }
+    };
+    match (*network).add_synaptic_scaling(scaling) {
+        Ok(_) => true,
+        Err(e) => {
+            set_last_error(e);
+            false
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn Network_clear_synaptic_scalings() {
+    let network = NETWORK.clone();
+    let mut network = network.lock().unwrap();
+    (*network).clear_synaptic_scalings();
+}
+
+// Freezes or resumes learning on every plastic connection.
+#[no_mangle]
+pub extern "C" fn Network_set_plasticity(enabled: bool) {
+    let network = NETWORK.clone();
+    let mut network = network.lock().unwrap();
+    // switching all connections cannot fail
+    let _ = (*network).set_plasticity(None, enabled);
+}
+
+#[no_mangle]
+pub extern "C" fn Network_set_projection_plasticity(
+    conn_ids_buf: *const c_char,
+    enabled: bool,
+) -> bool {
+    let network = NETWORK.clone();
+    let mut network = network.lock().unwrap();
+    let conn_ids_str = try_ffi!(c_str(conn_ids_buf), false);
+    let conn_ids: Vec<Num> = match serde_json::from_str(conn_ids_str) {
+        Ok(conn_ids) => conn_ids,
+        Err(e) => {
+            set_last_error(format!("invalid connection ids: {}", e));
+            return false;
+        }
+    };
+    match (*network).set_plasticity(Some(&conn_ids), enabled) {
+        Ok(()) => true,
+        Err(e) => {
+            set_last_error(e);
+            false
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn Network_reset_state(reset_thresholds: bool, rewind_clock: bool) {
+    let network = NETWORK.clone();
+    let mut network = network.lock().unwrap();
+    (*network).reset_state_with(&ResetOptions {
+        reset_thresholds,
+        rewind_clock,
+    });
+}
+
+#[no_mangle]
+pub extern "C" fn Network_set_rates(pop_id: usize, rates_buf: *const c_char) -> bool {
+    let network = NETWORK.clone();
+    let mut network = network.lock().unwrap();
+    let rates_str = try_ffi!(c_str(rates_buf), false);
+    let rates: Vec<f64> = match serde_json::from_str(rates_str) {
+        Ok(rates) => rates,
+        Err(e) => {
+            set_last_error(format!("invalid rates: {}", e));
+            return false;
+        }
+    };
+    let population = try_ffi!((*network).get_population_by_id(pop_id), false);
+    match (*network).set_rates(&population, rates) {
+        Ok(()) => true,
+        Err(e) => {
+            set_last_error(e);
+            false
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn Network_set_rate_schedule(pop_id: usize, schedule_buf: *const c_char) -> bool {
+    let network = NETWORK.clone();
+    let mut network = network.lock().unwrap();
+    let schedule_str = try_ffi!(c_str(schedule_buf), false);
+    let schedule: RateSchedule = match serde_json::from_str(schedule_str) {
+        Ok(schedule) => schedule,
+        Err(e) => {
+            set_last_error(format!("invalid rate schedule: {}", e));
+            return false;
+        }
+    };
+    let population = try_ffi!((*network).get_population_by_id(pop_id), false);
+    match (*network).set_rate_schedule(&population, &schedule) {
+        Ok(()) => true,
+        Err(e) => {
+            set_last_error(e);
+            false
+        }
+    }
+}
+
+// Encodes a JSON array of values with a JSON encoder description into the
+// spike trains of a population of spike generators, for a window of
+// `duration` ms starting at the current time.
+#[no_mangle]
+pub extern "C" fn Network_encode(
+    pop_id: usize,
+    coding_buf: *const c_char,
+    values_buf: *const c_char,
+    duration: Time,
+) -> bool {
+    let network = NETWORK.clone();
+    let mut network = network.lock().unwrap();
+    let coding_str = try_ffi!(c_str(coding_buf), false);
+    let coding: Coding = match serde_json::from_str(coding_str) {
+        Ok(coding) => coding,
+        Err(e) => {
+            set_last_error(format!("invalid encoder: {}", e));
+            return false;
+        }
+    };
+    let values_str = try_ffi!(c_str(values_buf), false);
+    let values: Vec<Double> = match serde_json::from_str(values_str) {
+        Ok(values) => values,
+        Err(e) => {
+            set_last_error(format!("invalid values: {}", e));
+            return false;
+        }
+    };
+    let population = try_ffi!((*network).get_population_by_id(pop_id), false);
+    match (*network).encode(&population, &coding, &values, duration) {
+        Ok(()) => true,
+        Err(e) => {
+            set_last_error(e);
+            false
+        }
+    }
 }
 
 #[no_mangle]
 pub extern "C" fn Network_get_property(pop_id: usize, name_buf: *const c_char) -> *mut c_char {
     let network = NETWORK.clone();
     let network = network.lock().unwrap();
-    let name_str: &CStr = unsafe { CStr::from_ptr(name_buf) };
-    let name: String = name_str.to_str().unwrap().to_owned().parse().unwrap();
-    let result = (*network).get_property(pop_id, name);
+    let name_str = try_ffi!(c_str(name_buf), std::ptr::null_mut());
+    let name: String = name_str.to_owned();
+    let population = try_ffi!(
+        (*network).get_population_by_id(pop_id),
+        std::ptr::null_mut()
+    );
+    match (*network).get_property(&population, name) {
+        Ok(result) => {
+            let ret = CString::new(serde_json::to_string(&result).unwrap()).unwrap();
+            ret.into_raw()
+        }
+        Err(e) => {
+            set_last_error(e);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn Network_get_property_names(neuron_id: Num) -> *mut c_char {
+    let network = NETWORK.clone();
+    let network = network.lock().unwrap();
+    let result = try_ffi!((*network).property_names(neuron_id), std::ptr::null_mut());
     let ret = CString::new(serde_json::to_string(&result).unwrap()).unwrap();
     ret.into_raw()
 }
+
+#[no_mangle]
+pub extern "C" fn Network_set_integration(pop_id: usize, method_buf: *const c_char) -> bool {
+    let network = NETWORK.clone();
+    let mut network = network.lock().unwrap();
+    let method_str = try_ffi!(c_str(method_buf), false);
+    let method: Integration = match method_str.parse() {
+        Ok(method) => method,
+        Err(_) => {
+            set_last_error(format!("unknown integration method {}", method_str));
+            return false;
+        }
+    };
+    let population = try_ffi!((*network).get_population_by_id(pop_id), false);
+    match (*network).set_integration(&population, method) {
+        Ok(()) => true,
+        Err(e) => {
+            set_last_error(e);
+            false
+        }
+    }
+}
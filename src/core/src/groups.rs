@@ -0,0 +1,128 @@
+// Copyright (c) 2019 Libgirl
+//
+// Released under Apache 2.0 license as described in the file LICENSE.txt.
+
+// Neuron groups: populations of one model whose state is kept in contiguous
+// arrays and advanced by a single `update_group` call per step, which the
+// compiler can vectorize, instead of one virtual call and parameter lookup
+// per neuron. Groups are created with `Network::create_group`; other models,
+// including those registered from outside the crate, go through `Neuron`.
+use crate::events::SpikeEvent;
+use crate::models::{lif, static_poisson, Neuron, NeuronActivity};
+use crate::{Double, Index, Parameters, Time};
+
+pub trait NeuronGroup: std::marker::Send {
+    fn name(&self) -> String;
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Advances every neuron over the step starting at `t` and appends the
+    // indices of those that fire, in increasing order.
+    fn update_group(&mut self, t: Time, fired: &mut Vec<usize>);
+    fn handle_spike(&mut self, i: usize, event: SpikeEvent);
+    fn reset_state(&mut self);
+
+    fn property_names(&self) -> Vec<String>;
+    fn get_property(&self, i: usize, name: String) -> Result<Double, String>;
+    fn set_property(&mut self, i: usize, name: String, value: Double) -> Result<(), String>;
+}
+
+// Models with a group implementation.
+pub fn build(
+    model: &str,
+    size: usize,
+    params: &Parameters,
+) -> Result<Box<dyn NeuronGroup>, String> {
+    let synapse = match model {
+        "iaf_psc_delta" => lif::Synapse::Delta,
+        "iaf_psc_exp" => lif::Synapse::CurrentExp,
+        "iaf_psc_alpha" => lif::Synapse::CurrentAlpha,
+        "StaticPoisson" => return Ok(Box::new(static_poisson::Group::new(size, params)?)),
+        _ => return Err(format!("{} has no group implementation", model)),
+    };
+    Ok(Box::new(lif::Group::new(size, params, synapse)?))
+}
+
+// Stands for a neuron of a group among the neurons of the network, keeping
+// its id and spike records. Its state lives in the group, which decides when
+// it fires: the network then updates the member so that it emits the spike.
+pub struct GroupMember {
+    name: String,
+    nid: Index,
+    is_record_spikes: bool,
+    spike_records: Vec<Vec<Time>>,
+}
+
+impl GroupMember {
+    pub fn new(name: String) -> GroupMember {
+        GroupMember {
+            name,
+            nid: -1,
+            is_record_spikes: false,
+            spike_records: Vec::new(),
+        }
+    }
+}
+
+impl Neuron for GroupMember {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn update(&mut self, t: Double) -> NeuronActivity {
+        if self.is_record_spikes {
+            let spike_record_index = self.spike_records.len() - 1;
+            self.spike_records[spike_record_index].push(t);
+        }
+        NeuronActivity::Fires(SpikeEvent::new())
+    }
+
+    // Inputs are delivered to the group.
+    fn handle_spike(&mut self, _event: SpikeEvent) {}
+
+    fn set_neuron_id(&mut self, nid: i64) {
+        self.nid = nid;
+    }
+
+    fn set_params(&mut self, _params: &Parameters) -> Result<(), String> {
+        Err(format!(
+            "neurons of a {} group are set through their properties",
+            self.name
+        ))
+    }
+
+    fn neuron_id(&self) -> i64 {
+        self.nid
+    }
+
+    fn new_spike_record(&mut self) {
+        self.spike_records.push(Vec::new());
+    }
+
+    fn set_spike_recording(&mut self, is_on: bool) {
+        self.is_record_spikes = is_on;
+    }
+
+    fn clear_spike_records(&mut self) {
+        self.spike_records.clear();
+    }
+
+    fn get_spike_records(&self) -> Vec<Vec<Time>> {
+        self.spike_records.clone()
+    }
+
+    // Properties are read and set through the group.
+    fn property_names(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn get_property(&self, name: String) -> Result<Double, String> {
+        Err(format!("{} is a property of the {} group", name, self.name))
+    }
+
+    fn set_property(&mut self, name: String, _value: Double) -> Result<(), String> {
+        Err(format!("{} is a property of the {} group", name, self.name))
+    }
+}
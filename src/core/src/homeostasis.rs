@@ -0,0 +1,318 @@
+// Copyright (c) 2019 Libgirl
+//
+// Released under Apache 2.0 license as described in the file LICENSE.txt.
+
+// Homeostatic mechanisms keeping activity in range: an adaptive threshold
+// that can be attached to any model with a threshold property, and synaptic
+// scaling of projections toward a target firing rate.
+use crate::events::SpikeEvent;
+use crate::models::inhomogeneous_poisson::RateSchedule;
+use crate::models::{Integration, Neuron, NeuronActivity};
+use crate::network::Network;
+use crate::{Double, Index, Num, Parameters, Time};
+use std::collections::HashMap;
+
+// The threshold rises by `theta_plus` at each spike and decays back with the
+// time constant `tau_theta`, as `theta` of `cb_ath_lif`. The threshold is the
+// model property `property`, by default the threshold property of the model:
+// `v_th`, the `v_t` of AdEx or the threshold current `i_th` of Izhikevich.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ThresholdAdaptation {
+    pub theta_plus: Double,
+    #[serde(default = "default_tau_theta")]
+    pub tau_theta: Time,
+    #[serde(default)]
+    pub property: Option<String>,
+}
+
+fn default_tau_theta() -> Time {
+    1e7
+}
+
+impl ThresholdAdaptation {
+    pub fn new(theta_plus: Double, tau_theta: Time) -> ThresholdAdaptation {
+        ThresholdAdaptation {
+            theta_plus,
+            tau_theta,
+            property: None,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.theta_plus.is_finite() {
+            return Err(format!("invalid theta_plus {}", self.theta_plus));
+        }
+        if self.tau_theta <= 0. {
+            return Err(format!("invalid time constant {}", self.tau_theta));
+        }
+        Ok(())
+    }
+
+    // Name of the threshold property of a neuron.
+    pub fn threshold_property(&self, neuron: &dyn Neuron) -> Result<String, String> {
+        let names = neuron.property_names();
+        match &self.property {
+            Some(property) if names.contains(property) => Ok(property.clone()),
+            Some(property) => Err(format!("{} has no property {}", neuron.name(), property)),
+            None => neuron
+                .threshold_property()
+                .ok_or_else(|| format!("{} has no threshold property", neuron.name())),
+        }
+    }
+
+    // Wraps a neuron, whose threshold then becomes the base threshold.
+    pub fn attach(&self, neuron: Box<dyn Neuron>) -> Result<Box<dyn Neuron>, String> {
+        self.validate()?;
+        let property = self.threshold_property(&*neuron)?;
+        let base = neuron.get_property(property.clone())?;
+        Ok(Box::new(AdaptiveThreshold {
+            inner: neuron,
+            property,
+            base,
+            theta: 0.,
+            theta_plus: self.theta_plus,
+            tau_theta: self.tau_theta,
+            fix_theta: false,
+        }))
+    }
+}
+
+// A neuron whose threshold property is kept at `base + theta`. It has the
+// properties of the wrapped model plus `theta`, `theta_plus`, `tau_theta`
+// and `fix_theta`, which freezes theta when set to 1.
+pub struct AdaptiveThreshold {
+    inner: Box<dyn Neuron>,
+    property: String,
+    base: Double,
+    theta: Double,
+    theta_plus: Double,
+    tau_theta: Time,
+    fix_theta: bool,
+}
+
+const ADAPTATION_PROPERTIES: [&str; 4] = ["theta", "theta_plus", "tau_theta", "fix_theta"];
+
+impl Neuron for AdaptiveThreshold {
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn update(&mut self, t: Double) -> NeuronActivity {
+        if !self.fix_theta {
+            self.theta *= (-Network::resolution() / self.tau_theta).exp();
+        }
+        // the property was checked when attached
+        let _ = self
+            .inner
+            .set_property(self.property.clone(), self.base + self.theta);
+        let activity = self.inner.update(t);
+        if let NeuronActivity::Fires(_) = activity {
+            if !self.fix_theta {
+                self.theta += self.theta_plus;
+            }
+        }
+        activity
+    }
+
+    fn handle_spike(&mut self, event: SpikeEvent) {
+        self.inner.handle_spike(event);
+    }
+
+    fn set_neuron_id(&mut self, nid: i64) {
+        self.inner.set_neuron_id(nid);
+    }
+
+    fn set_params(&mut self, params: &Parameters) -> Result<(), String> {
+        self.inner.set_params(params)?;
+        if params.contains_key(&self.property) {
+            self.base = self.inner.get_property(self.property.clone())?;
+        }
+        Ok(())
+    }
+
+    fn neuron_id(&self) -> i64 {
+        self.inner.neuron_id()
+    }
+
+    fn new_spike_record(&mut self) {
+        self.inner.new_spike_record();
+    }
+
+    fn set_spike_recording(&mut self, is_on: bool) {
+        self.inner.set_spike_recording(is_on);
+    }
+
+    fn clear_spike_records(&mut self) {
+        self.inner.clear_spike_records();
+    }
+
+    fn get_spike_records(&self) -> Vec<Vec<Time>> {
+        self.inner.get_spike_records()
+    }
+
+    fn check_integration(&self, method: Integration) -> Result<(), String> {
+        self.inner.check_integration(method)
+    }
+
+    fn set_integration(&mut self, method: Integration) -> Result<(), String> {
+        self.inner.set_integration(method)
+    }
+
+    fn set_precise_timing(&mut self, enabled: bool) -> Result<(), String> {
+        self.inner.set_precise_timing(enabled)
+    }
+
+    fn check_spike_times(&self, times: &[Time]) -> Result<(), String> {
+        self.inner.check_spike_times(times)
+    }
+
+    fn set_spike_times(&mut self, times: &[Time]) -> Result<(), String> {
+        self.inner.set_spike_times(times)
+    }
+
+    fn set_input_current(&mut self, current: Double) -> Result<(), String> {
+        self.inner.set_input_current(current)
+    }
+
+    fn check_rate_schedule(&self, schedule: &RateSchedule) -> Result<(), String> {
+        self.inner.check_rate_schedule(schedule)
+    }
+
+    fn set_rate_schedule(&mut self, schedule: &RateSchedule) -> Result<(), String> {
+        self.inner.set_rate_schedule(schedule)
+    }
+
+    fn reset_state(&mut self, reset_thresholds: bool) {
+        self.inner.reset_state(reset_thresholds);
+        if reset_thresholds {
+            self.theta = 0.;
+        }
+    }
+
+    fn threshold_property(&self) -> Option<String> {
+        Some(self.property.clone())
+    }
+
+    fn property_names(&self) -> Vec<String> {
+        let mut names = self.inner.property_names();
+        names.extend(ADAPTATION_PROPERTIES.iter().map(|s| s.to_string()));
+        names
+    }
+
+    // The threshold property reads and sets the base threshold.
+    fn get_property(&self, name: String) -> Result<Double, String> {
+        match name.as_ref() {
+            "theta" => Ok(self.theta),
+            "theta_plus" => Ok(self.theta_plus),
+            "tau_theta" => Ok(self.tau_theta),
+            "fix_theta" => Ok(if self.fix_theta { 1. } else { 0. }),
+            _ if name == self.property => Ok(self.base),
+            _ => self.inner.get_property(name),
+        }
+    }
+
+    fn set_property(&mut self, name: String, value: Double) -> Result<(), String> {
+        match name.as_ref() {
+            "theta" => self.theta = value,
+            "theta_plus" => self.theta_plus = value,
+            "tau_theta" if value > 0. => self.tau_theta = value,
+            "tau_theta" => return Err(format!("invalid time constant {}", value)),
+            "fix_theta" => self.fix_theta = value >= 0.5,
+            _ if name == self.property => {
+                self.inner.set_property(name, value + self.theta)?;
+                self.base = value;
+            }
+            _ => return self.inner.set_property(name, value),
+        }
+        Ok(())
+    }
+}
+
+// Multiplies the incoming weights of the targets of a projection every
+// `interval` ms by `exp(gain * (target_rate - rate) / target_rate)`, where
+// rate is the firing rate of the target over the interval, in Hertz. Being
+// exponential, the scaling never turns a weight to zero.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SynapticScaling {
+    pub connections: Vec<Num>,
+    pub target_rate: Double,
+    pub interval: Time,
+    #[serde(default = "default_gain")]
+    pub gain: Double,
+}
+
+fn default_gain() -> Double {
+    0.1
+}
+
+impl SynapticScaling {
+    pub fn new(connections: Vec<Num>, target_rate: Double, interval: Time) -> SynapticScaling {
+        SynapticScaling {
+            connections,
+            target_rate,
+            interval,
+            gain: default_gain(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.target_rate <= 0. {
+            return Err(format!("invalid target rate {}", self.target_rate));
+        }
+        if self.interval <= 0. {
+            return Err(format!("invalid scaling interval {}", self.interval));
+        }
+        if self.gain < 0. {
+            return Err(format!("invalid gain {}", self.gain));
+        }
+        Ok(())
+    }
+
+    pub fn factor(&self, rate: Double) -> Double {
+        (self.gain * (self.target_rate - rate) / self.target_rate).exp()
+    }
+}
+
+// A synaptic scaling counting the spikes of its targets until it is due.
+pub(crate) struct ScalingState {
+    pub scaling: SynapticScaling,
+    pub due: Time,
+    pub counts: HashMap<Index, usize>,
+}
+
+impl ScalingState {
+    pub fn new(scaling: SynapticScaling, targets: Vec<Index>, start: Time) -> ScalingState {
+        ScalingState {
+            due: start + scaling.interval,
+            counts: targets.into_iter().map(|t| (t, 0)).collect(),
+            scaling,
+        }
+    }
+
+    pub fn record_spike(&mut self, neuron_id: Index) {
+        if let Some(count) = self.counts.get_mut(&neuron_id) {
+            *count += 1;
+        }
+    }
+
+    pub fn rewind(&mut self) {
+        self.due = self.scaling.interval;
+        for count in self.counts.values_mut() {
+            *count = 0;
+        }
+    }
+
+    // Scaling factors of the targets, resetting the counts.
+    pub fn factors(&mut self) -> HashMap<Index, Double> {
+        let interval = self.scaling.interval;
+        let scaling = &self.scaling;
+        self.counts
+            .iter_mut()
+            .map(|(&target, count)| {
+                let rate = *count as Double * 1000. / interval;
+                *count = 0;
+                (target, scaling.factor(rate))
+            })
+            .collect()
+    }
+}
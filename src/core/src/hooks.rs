@@ -0,0 +1,181 @@
+// Copyright (c) 2019 Libgirl
+//
+// Released under Apache 2.0 license as described in the file LICENSE.txt.
+
+// Step hooks: functions called during runs with mutable access to the
+// network, every few ms of simulated time or after the steps where given
+// neurons fire, to script protocols such as changing the stimulation or
+// stopping early without splitting runs.
+use crate::network::Network;
+use crate::populations::Population;
+use crate::{Double, Num, Time};
+use std::collections::HashSet;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RunControl {
+    Continue,
+    // ends the run after the current step
+    Stop,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookTrigger {
+    // every given number of ms of simulated time
+    Every(Time),
+    // after the steps where one of the neurons fires
+    Spikes(Vec<Num>),
+}
+
+impl HookTrigger {
+    pub fn validate(&self, neurons: usize) -> Result<(), String> {
+        match self {
+            HookTrigger::Every(interval) if *interval <= 0. => {
+                Err(format!("invalid hook interval {}", interval))
+            }
+            HookTrigger::Spikes(ids) => match ids.iter().find(|&&id| id >= neurons) {
+                Some(id) => Err(format!("unknown neuron {}", id)),
+                None => Ok(()),
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+// Called with the network and the time at the end of the step.
+pub type StepHook = Box<dyn FnMut(&mut Network, Time) -> RunControl + Send>;
+
+pub(crate) struct HookState {
+    pub id: Num,
+    pub hook: StepHook,
+    trigger: HookTrigger,
+    neurons: HashSet<Num>,
+    due: Time,
+    fired: bool,
+}
+
+impl HookState {
+    pub fn new(id: Num, trigger: HookTrigger, hook: StepHook, start: Time) -> HookState {
+        let (neurons, due) = match &trigger {
+            HookTrigger::Every(interval) => (HashSet::new(), start + interval),
+            HookTrigger::Spikes(ids) => (ids.iter().cloned().collect(), start),
+        };
+        HookState {
+            id,
+            hook,
+            trigger,
+            neurons,
+            due,
+            fired: false,
+        }
+    }
+
+    pub fn record_spike(&mut self, neuron: Num) {
+        if self.neurons.contains(&neuron) {
+            self.fired = true;
+        }
+    }
+
+    pub fn is_due(&self, t: Time) -> bool {
+        match self.trigger {
+            // half a step of tolerance against rounding
+            HookTrigger::Every(_) => t + Network::resolution() / 2. >= self.due,
+            HookTrigger::Spikes(_) => self.fired,
+        }
+    }
+
+    pub fn call(&mut self, network: &mut Network, t: Time) -> RunControl {
+        match self.trigger {
+            HookTrigger::Every(interval) => self.due += interval,
+            HookTrigger::Spikes(_) => self.fired = false,
+        }
+        (self.hook)(network, t)
+    }
+
+    pub fn rewind(&mut self) {
+        if let HookTrigger::Every(interval) = self.trigger {
+            self.due = interval;
+        }
+        self.fired = false;
+    }
+}
+
+// Conditions ending `Network::run_until`, checked at the end of each step.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "condition", rename_all = "snake_case")]
+pub enum StopCondition {
+    // the neurons have fired `count` spikes in total
+    SpikeCount {
+        neurons: Vec<Num>,
+        count: usize,
+    },
+    // one of the neurons fires
+    Fires {
+        neurons: Vec<Num>,
+    },
+    // a property of a neuron reaches `value` from below, or from above when
+    // `falling`
+    Crosses {
+        neuron: Num,
+        property: String,
+        value: Double,
+        #[serde(default)]
+        falling: bool,
+    },
+}
+
+impl StopCondition {
+    pub fn spike_count<P: AsRef<Population>>(pop: &P, count: usize) -> StopCondition {
+        StopCondition::SpikeCount {
+            neurons: pop.as_ref().iter().map(|n| n as Num).collect(),
+            count,
+        }
+    }
+
+    pub fn fires<P: AsRef<Population>>(pop: &P) -> StopCondition {
+        StopCondition::Fires {
+            neurons: pop.as_ref().iter().map(|n| n as Num).collect(),
+        }
+    }
+
+    pub fn crosses(neuron: Num, property: &str, value: Double) -> StopCondition {
+        StopCondition::Crosses {
+            neuron,
+            property: property.to_string(),
+            value,
+            falling: false,
+        }
+    }
+
+    // Neurons whose spikes are counted.
+    pub fn watched_neurons(&self) -> &[Num] {
+        match self {
+            StopCondition::SpikeCount { neurons, .. } | StopCondition::Fires { neurons } => neurons,
+            StopCondition::Crosses { .. } => &[],
+        }
+    }
+
+    // Whether the condition holds given the spikes counted since the start
+    // and the previous and current values of the property.
+    pub fn is_met(&self, spikes: usize, previous: Double, current: Double) -> bool {
+        match self {
+            StopCondition::SpikeCount { count, .. } => spikes >= *count,
+            StopCondition::Fires { .. } => spikes > 0,
+            StopCondition::Crosses { value, falling, .. } => {
+                if *falling {
+                    previous > *value && current <= *value
+                } else {
+                    previous < *value && current >= *value
+                }
+            }
+        }
+    }
+}
+
+// Progress of a stop condition over the parts of a run, see
+// `Network::watch`.
+pub struct ConditionWatch {
+    pub condition: StopCondition,
+    pub met: Option<Time>,
+    pub(crate) previous: Double,
+}
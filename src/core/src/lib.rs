@@ -14,15 +14,24 @@ extern crate rand;
 
 use std::collections::HashMap;
 
+pub mod connection_spec;
 pub mod connection_supervisor;
 pub mod connections;
 pub mod connectors;
+pub mod datasets;
+pub mod devices;
+pub mod encoders;
 pub mod events;
+pub mod groups;
+pub mod hooks;
+pub mod homeostasis;
 pub mod models;
 pub mod network;
 pub mod node;
-mod ode;
+pub mod ode;
+pub mod parameters;
 pub mod populations;
+pub mod readout;
 mod utils;
 pub mod ffi;
 
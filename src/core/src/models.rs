@@ -3,6 +3,7 @@
 // Released under Apache 2.0 license as described in the file LICENSE.txt.
 
 use crate::events::SpikeEvent;
+use crate::models::inhomogeneous_poisson::RateSchedule;
 use crate::{Double, Parameters, Time};
 
 pub enum NeuronActivity {
@@ -16,30 +17,143 @@ pub trait Neuron: std::marker::Send {
     fn handle_spike(&mut self, event: SpikeEvent);
 
     fn set_neuron_id(&mut self, nid: i64);
-    fn set_params(&mut self, params: &Parameters);
+    fn set_params(&mut self, params: &Parameters) -> Result<(), String>;
     fn neuron_id(&self) -> i64;
     fn new_spike_record(&mut self);
     fn set_spike_recording(&mut self, is_on: bool);
     fn clear_spike_records(&mut self);
     fn get_spike_records(&self) -> Vec<Vec<Time>>;
 
-    fn get_property(&self, name: String) -> Double;
-    fn set_property(&mut self, name: String, value: Double);
+    // Models integrate with RungeKutta4 unless they support another method.
+    fn check_integration(&self, method: Integration) -> Result<(), String> {
+        match method {
+            Integration::RungeKutta4 => Ok(()),
+            _ => Err(format!(
+                "{} does not support {} integration",
+                self.name(),
+                method
+            )),
+        }
+    }
+
+    fn set_integration(&mut self, method: Integration) -> Result<(), String> {
+        self.check_integration(method)
+    }
+
+    // Models that can time their spikes within a step override this; the
+    // others emit spikes at the beginning of the step and refuse it.
+    fn set_precise_timing(&mut self, enabled: bool) -> Result<(), String> {
+        if enabled {
+            return Err(format!(
+                "{} cannot time its spikes within a step",
+                self.name()
+            ));
+        }
+        Ok(())
+    }
+
+    // Only spike generators accept explicit spike trains.
+    fn check_spike_times(&self, _times: &[Time]) -> Result<(), String> {
+        Err(format!("{} does not accept spike times", self.name()))
+    }
+
+    fn set_spike_times(&mut self, times: &[Time]) -> Result<(), String> {
+        self.check_spike_times(times)
+    }
+
+    // Current injected by devices during the next step, in addition to i_e.
+    fn set_input_current(&mut self, _current: Double) -> Result<(), String> {
+        Err(format!("{} does not accept input currents", self.name()))
+    }
+
+    // Only Poisson generators with a time-varying rate accept schedules.
+    fn check_rate_schedule(&self, _schedule: &RateSchedule) -> Result<(), String> {
+        Err(format!("{} does not accept rate schedules", self.name()))
+    }
+
+    fn set_rate_schedule(&mut self, schedule: &RateSchedule) -> Result<(), String> {
+        self.check_rate_schedule(schedule)
+    }
+
+    // Brings the dynamic variables back to their initial values, keeping the
+    // parameters. Adaptive thresholds are only reset when asked to.
+    fn reset_state(&mut self, _reset_thresholds: bool) {}
+
+    // Number of coming steps, starting at `t`, during which the neuron cannot
+    // fire unless it receives input, None when it never will. The
+    // event-driven engine only updates a neuron when it receives input or
+    // this runs out, so models without an analytic solution are updated at
+    // every step.
+    fn quiet_steps(&self, _t: Time) -> Option<usize> {
+        Some(0)
+    }
+
+    // Advances the neuron over `steps` steps without input nor spike at
+    // once. Only called within the quiet steps.
+    fn skip(&mut self, _steps: usize) {}
+
+    // Property raised by adaptive thresholds, v_th or v_t when the model
+    // has one.
+    fn threshold_property(&self) -> Option<String> {
+        let names = self.property_names();
+        ["v_th", "v_t"]
+            .iter()
+            .find(|name| names.iter().any(|n| n == *name))
+            .map(|name| name.to_string())
+    }
+
+    fn property_names(&self) -> Vec<String>;
+    fn get_property(&self, name: String) -> Result<Double, String>;
+    fn set_property(&mut self, name: String, value: Double) -> Result<(), String>;
+}
+
+// Fails on the first key that is not one of the model's parameters.
+pub fn check_params(params: &Parameters, known: &Parameters) -> Result<(), String> {
+    for key in params.keys() {
+        if !known.contains_key(key) {
+            return Err(format!("unknown parameter {}", key));
+        }
+    }
+    Ok(())
+}
+
+pub fn unknown_property(name: &str) -> String {
+    format!("unknown property {}", name)
+}
+
+custom_derive! {
+    #[derive(Copy, Clone, Debug, PartialEq, EnumDisplay, EnumFromStr)]
+    pub enum Integration {
+        RungeKutta4,
+        // adaptive Dormand-Prince substeps with local error control
+        RungeKutta45,
+        // exact relaxation of each variable with rates frozen over the step
+        ExponentialEuler,
+        // propagator matrix solution, for models with linear subthreshold dynamics
+        Exact,
+    }
 }
 
 custom_derive! {
-    #[derive(Copy, Clone, EnumFromStr)]
+    #[derive(Copy, Clone, EnumDisplay, EnumFromStr)]
     pub enum NeuronType {
         HodgkinHuxley,
         IAF,
         Izhikevich,
         StaticPoisson,
         ConductionBasedAdaptiveThresholdLIF,
+        AdEx,
+        AdExCurrentBased,
     }
 }
 
+pub mod adex;
 pub mod cb_ath_lif;
 pub mod hodgkin_huxley;
 pub mod iaf;
+pub mod inhomogeneous_poisson;
 pub mod izhikevich;
+pub mod lif;
+pub mod registry;
+pub mod spike_generator;
 pub mod static_poisson;
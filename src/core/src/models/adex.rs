@@ -0,0 +1,389 @@
+// Copyright (c) 2019 Libgirl
+//
+// Released under Apache 2.0 license as described in the file LICENSE.txt.
+
+// Adaptive exponential integrate-and-fire model (Brette & Gerstner, 2005)
+// Units: mV, ms, pF, nS and pA. Positive weights are excitatory, negative
+// weights inhibitory.
+use crate::events::{Event, SpikeEvent};
+use crate::models::{check_params, unknown_property, Integration, Neuron, NeuronActivity};
+use crate::network::Network;
+use crate::ode::{rk45_system_until, rk4_system, DEFAULT_TOLERANCE};
+use crate::{Double, Index, Parameters, Time};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Synapse {
+    Conductance, // weights in nS, exponentially decaying conductances
+    Current,     // weights in pA, exponentially decaying currents
+}
+
+#[derive(Debug)]
+pub struct Model {
+    pub c_m: Double,        // Membrane capacitance
+    pub g_l: Double,        // Leak conductance
+    pub e_l: Double,        // Leak reversal potential
+    pub v_t: Double,        // Spike initiation threshold
+    pub delta_t: Double,    // Slope factor of the exponential term
+    pub v_peak: Double,     // Spike detection threshold
+    pub v_reset: Double,    // Reset potential
+    pub t_ref: Double,      // Refractory period
+    pub a: Double,          // Subthreshold adaptation
+    pub b: Double,          // Spike-triggered adaptation
+    pub tau_w: Double,      // Adaptation time constant
+    pub e_ex: Double,       // Excitatory reversal potential
+    pub e_in: Double,       // Inhibitory reversal potential
+    pub tau_syn_ex: Double, // Excitatory synaptic time constant
+    pub tau_syn_in: Double, // Inhibitory synaptic time constant
+    pub v: Double,          // Voltage
+    pub w: Double,          // Adaptation current
+    pub g_ex: Double,       // Excitatory conductance or current
+    pub g_in: Double,       // Inhibitory conductance or current
+    pub synapse: Synapse,
+    pub integration: Integration,
+    initial: [Double; 2], // v and w restored by reset_state
+    step: Double,
+    refractory_left: Double,
+    i_e: Double,
+    i_ext: Double, // current of the step injected by devices
+    nid: Index,
+    is_record_spikes: bool,
+    spike_records: Vec<Vec<Time>>,
+}
+
+impl Model {
+    pub fn parameters() -> Parameters {
+        let mut params = Parameters::new();
+        params.insert("v_m".to_string(), -70.6);
+        params.insert("w".to_string(), 0.);
+        params.insert("c_m".to_string(), 281.);
+        params.insert("g_l".to_string(), 30.);
+        params.insert("e_l".to_string(), -70.6);
+        params.insert("v_t".to_string(), -50.4);
+        params.insert("delta_t".to_string(), 2.);
+        params.insert("v_peak".to_string(), 0.);
+        params.insert("v_reset".to_string(), -60.);
+        params.insert("t_ref".to_string(), 0.);
+        params.insert("a".to_string(), 4.);
+        params.insert("b".to_string(), 80.5);
+        params.insert("tau_w".to_string(), 144.);
+        params.insert("e_ex".to_string(), 0.);
+        params.insert("e_in".to_string(), -85.);
+        params.insert("tau_syn_ex".to_string(), 0.2);
+        params.insert("tau_syn_in".to_string(), 2.);
+        params.insert("i_e".to_string(), 0.);
+        params
+    }
+
+    fn get_or_default(params: &Parameters, key: &str) -> f64 {
+        let default_params = Model::parameters();
+        params
+            .get(key)
+            .cloned()
+            .unwrap_or(*default_params.get(key).unwrap())
+    }
+
+    pub fn new(params: &Parameters, synapse: Synapse) -> Model {
+        Model {
+            c_m: Model::get_or_default(params, "c_m"),
+            g_l: Model::get_or_default(params, "g_l"),
+            e_l: Model::get_or_default(params, "e_l"),
+            v_t: Model::get_or_default(params, "v_t"),
+            delta_t: Model::get_or_default(params, "delta_t"),
+            v_peak: Model::get_or_default(params, "v_peak"),
+            v_reset: Model::get_or_default(params, "v_reset"),
+            t_ref: Model::get_or_default(params, "t_ref"),
+            a: Model::get_or_default(params, "a"),
+            b: Model::get_or_default(params, "b"),
+            tau_w: Model::get_or_default(params, "tau_w"),
+            e_ex: Model::get_or_default(params, "e_ex"),
+            e_in: Model::get_or_default(params, "e_in"),
+            tau_syn_ex: Model::get_or_default(params, "tau_syn_ex"),
+            tau_syn_in: Model::get_or_default(params, "tau_syn_in"),
+            v: Model::get_or_default(params, "v_m"),
+            w: Model::get_or_default(params, "w"),
+            g_ex: 0.,
+            g_in: 0.,
+            synapse,
+            integration: Integration::RungeKutta4,
+            initial: [
+                Model::get_or_default(params, "v_m"),
+                Model::get_or_default(params, "w"),
+            ],
+            step: 0.,
+            refractory_left: 0.,
+            i_e: Model::get_or_default(params, "i_e"),
+            i_ext: 0.,
+            nid: -1,
+            is_record_spikes: false,
+            spike_records: Vec::new(),
+        }
+    }
+
+    fn add_spike(&mut self, e: &SpikeEvent) {
+        let w = e.weight() * e.multiplicity();
+        if w > 0. {
+            self.g_ex += w;
+        } else {
+            self.g_in -= w;
+        }
+    }
+}
+
+impl Default for Model {
+    fn default() -> Model {
+        Model::new(&Model::parameters(), Synapse::Conductance)
+    }
+}
+
+impl Neuron for Model {
+    fn name(&self) -> String {
+        match self.synapse {
+            Synapse::Conductance => String::from("AdEx"),
+            Synapse::Current => String::from("AdExCurrentBased"),
+        }
+    }
+
+    fn set_params(&mut self, params: &Parameters) -> Result<(), String> {
+        check_params(params, &Model::parameters())?;
+        for (key, &value) in params {
+            let name = match key.as_ref() {
+                "v_m" => "v",
+                k => k,
+            };
+            self.set_property(name.to_string(), value)?;
+        }
+        if let Some(&v_m) = params.get("v_m") {
+            self.initial[0] = v_m;
+        }
+        if let Some(&w) = params.get("w") {
+            self.initial[1] = w;
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, t: Double) -> NeuronActivity {
+        let dt = Network::resolution();
+        let g_ex = self.g_ex;
+        let g_in = self.g_in;
+
+        let c_m = self.c_m;
+        let g_l = self.g_l;
+        let e_l = self.e_l;
+        let v_t = self.v_t;
+        let delta_t = self.delta_t;
+        let v_peak = self.v_peak;
+        let a = self.a;
+        let tau_w = self.tau_w;
+        let e_ex = self.e_ex;
+        let e_in = self.e_in;
+        let i_e = self.i_e + self.i_ext;
+        let synapse = self.synapse;
+        let refractory = self.refractory_left > 0.;
+
+        // state vector: v, w
+        let mut y = [if refractory { self.v_reset } else { self.v }, self.w];
+        let derivatives = move |y: &[Double], dydt: &mut [Double]| {
+            // the membrane overshoots during a spike, so it is bounded at
+            // v_peak, which also keeps the exponential term finite
+            let v = y[0].min(v_peak);
+            let w = y[1];
+            dydt[0] = if refractory {
+                0.
+            } else {
+                let spike = if delta_t > 0. {
+                    g_l * delta_t * ((v - v_t) / delta_t).exp()
+                } else {
+                    0.
+                };
+                let i_syn = match synapse {
+                    Synapse::Conductance => g_ex * (e_ex - v) + g_in * (e_in - v),
+                    Synapse::Current => g_ex - g_in,
+                };
+                (-g_l * (v - e_l) + spike - w + i_syn + i_e) / c_m
+            };
+            dydt[1] = (a * (v - e_l) - w) / tau_w;
+        };
+        if self.integration == Integration::RungeKutta45 {
+            // the step ends with the spike
+            rk45_system_until(
+                derivatives,
+                &mut y,
+                dt,
+                &mut self.step,
+                DEFAULT_TOLERANCE,
+                |y| y[0] >= v_peak,
+            );
+        } else {
+            rk4_system(derivatives, &mut y, dt);
+        }
+        if refractory {
+            self.refractory_left -= dt;
+        }
+        let mut v = y[0];
+        let mut w = y[1];
+
+        // synaptic time constants may be shorter than the resolution, so the
+        // decay is applied exactly
+        self.g_ex = g_ex * (-dt / self.tau_syn_ex).exp();
+        self.g_in = g_in * (-dt / self.tau_syn_in).exp();
+
+        let mut activity = NeuronActivity::Silent;
+        if v >= v_peak {
+            let se = SpikeEvent::new();
+            activity = NeuronActivity::Fires(se);
+            v = self.v_reset;
+            w += self.b;
+            self.refractory_left = self.t_ref;
+            if self.is_record_spikes {
+                let spike_record_index = self.spike_records.len() - 1;
+                self.spike_records[spike_record_index].push(t);
+            }
+        }
+
+        self.v = v;
+        self.w = w;
+
+        activity
+    }
+
+    fn set_input_current(&mut self, current: Double) -> Result<(), String> {
+        self.i_ext = current;
+        Ok(())
+    }
+
+    fn check_integration(&self, method: Integration) -> Result<(), String> {
+        match method {
+            Integration::RungeKutta4 | Integration::RungeKutta45 => Ok(()),
+            _ => Err(format!(
+                "{} does not support {} integration",
+                self.name(),
+                method
+            )),
+        }
+    }
+
+    fn set_integration(&mut self, method: Integration) -> Result<(), String> {
+        self.check_integration(method)?;
+        self.integration = method;
+        Ok(())
+    }
+
+    fn reset_state(&mut self, _reset_thresholds: bool) {
+        self.v = self.initial[0];
+        self.w = self.initial[1];
+        self.step = 0.;
+        self.g_ex = 0.;
+        self.g_in = 0.;
+        self.refractory_left = 0.;
+        self.i_ext = 0.;
+    }
+
+    fn handle_spike(&mut self, event: SpikeEvent) {
+        self.add_spike(&event);
+    }
+
+    fn set_neuron_id(&mut self, nid: i64) {
+        self.nid = nid;
+    }
+
+    fn neuron_id(&self) -> i64 {
+        self.nid
+    }
+
+    fn new_spike_record(&mut self) {
+        self.spike_records.push(Vec::new());
+    }
+
+    fn set_spike_recording(&mut self, is_on: bool) {
+        self.is_record_spikes = is_on;
+    }
+
+    fn clear_spike_records(&mut self) {
+        self.spike_records.clear();
+    }
+
+    fn get_spike_records(&self) -> Vec<Vec<Time>> {
+        self.spike_records.clone()
+    }
+
+    fn property_names(&self) -> Vec<String> {
+        [
+            "v",
+            "w",
+            "g_ex",
+            "g_in",
+            "c_m",
+            "g_l",
+            "e_l",
+            "v_t",
+            "delta_t",
+            "v_peak",
+            "v_reset",
+            "t_ref",
+            "a",
+            "b",
+            "tau_w",
+            "e_ex",
+            "e_in",
+            "tau_syn_ex",
+            "tau_syn_in",
+            "i_e",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+    }
+
+    fn get_property(&self, name: String) -> Result<Double, String> {
+        match name.as_ref() {
+            "v" => Ok(self.v),
+            "w" => Ok(self.w),
+            "g_ex" => Ok(self.g_ex),
+            "g_in" => Ok(self.g_in),
+            "c_m" => Ok(self.c_m),
+            "g_l" => Ok(self.g_l),
+            "e_l" => Ok(self.e_l),
+            "v_t" => Ok(self.v_t),
+            "delta_t" => Ok(self.delta_t),
+            "v_peak" => Ok(self.v_peak),
+            "v_reset" => Ok(self.v_reset),
+            "t_ref" => Ok(self.t_ref),
+            "a" => Ok(self.a),
+            "b" => Ok(self.b),
+            "tau_w" => Ok(self.tau_w),
+            "e_ex" => Ok(self.e_ex),
+            "e_in" => Ok(self.e_in),
+            "tau_syn_ex" => Ok(self.tau_syn_ex),
+            "tau_syn_in" => Ok(self.tau_syn_in),
+            "i_e" => Ok(self.i_e),
+            _ => Err(unknown_property(&name)),
+        }
+    }
+
+    fn set_property(&mut self, name: String, value: Double) -> Result<(), String> {
+        match name.as_ref() {
+            "v" => self.v = value,
+            "w" => self.w = value,
+            "g_ex" => self.g_ex = value,
+            "g_in" => self.g_in = value,
+            "c_m" => self.c_m = value,
+            "g_l" => self.g_l = value,
+            "e_l" => self.e_l = value,
+            "v_t" => self.v_t = value,
+            "delta_t" => self.delta_t = value,
+            "v_peak" => self.v_peak = value,
+            "v_reset" => self.v_reset = value,
+            "t_ref" => self.t_ref = value,
+            "a" => self.a = value,
+            "b" => self.b = value,
+            "tau_w" => self.tau_w = value,
+            "e_ex" => self.e_ex = value,
+            "e_in" => self.e_in = value,
+            "tau_syn_ex" => self.tau_syn_ex = value,
+            "tau_syn_in" => self.tau_syn_in = value,
+            "i_e" => self.i_e = value,
+            _ => return Err(unknown_property(&name)),
+        }
+        Ok(())
+    }
+}
@@ -4,7 +4,7 @@
 
 // Conduction-based adaptive membrane threshold leaky integrate-and-fire  model
 use crate::events::{Event, SpikeEvent};
-use crate::models::{Neuron, NeuronActivity};
+use crate::models::{check_params, unknown_property, Neuron, NeuronActivity};
 use crate::network::Network;
 use crate::ode::rk4;
 use crate::{Double, Index, Parameters, Time};
@@ -30,6 +30,7 @@ pub struct Model {
     is_record_spikes: bool,
     fix_theta: Double, // actually a boolean. indicating theta is changing or not
     i_e: Double,
+    i_ext: Double, // current of the step injected by devices
     nid: Index,
     spike_records: Vec<Vec<Time>>,
 }
@@ -53,6 +54,7 @@ impl Model {
         params.insert("refact".to_string(), 5.);
         params.insert("ge".to_string(), 0.);
         params.insert("gi".to_string(), 0.);
+        params.insert("fix_theta".to_string(), 0.);
         params
     }
 
@@ -81,30 +83,32 @@ impl Model {
         let ge = Model::get_or_default(params, "ge");
         let gi = Model::get_or_default(params, "gi");
         let tau_m = Model::get_or_default(params, "tau_m");
+        let fix_theta = Model::get_or_default(params, "fix_theta");
 
         Model {
-            e_l: e_l,
-            reset_v: reset_v,
-            tau_m: tau_m,
+            e_l,
+            reset_v,
+            tau_m,
             v: v_m,
-            v_th: v_th,
-            i_e: i_e,
+            v_th,
+            i_e,
+            i_ext: 0.,
             nid: -1,
-            theta_plus: theta_plus,
-            theta: theta,
-            tau_theta: tau_theta,
-            tau_gi: tau_gi,
-            tau_ge: tau_ge,
-            e_i: e_i,
-            e_e: e_e,
-            refact: refact,
+            theta_plus,
+            theta,
+            tau_theta,
+            tau_gi,
+            tau_ge,
+            e_i,
+            e_e,
+            refact,
             during_refact: false,
             last_fire_t: 0.0,
-            ge: ge,
-            gi: gi,
+            ge,
+            gi,
             spike_records: Vec::new(),
             is_record_spikes: false,
-            fix_theta: 0.0,
+            fix_theta,
         }
     }
 
@@ -132,7 +136,32 @@ impl Neuron for Model {
         String::from("ConductionBasedAdaptiveThresholdLIF")
     }
 
-    fn set_params(&mut self, _params: &Parameters) {}
+    fn set_params(&mut self, params: &Parameters) -> Result<(), String> {
+        check_params(params, &Model::parameters())?;
+        for (key, &value) in params {
+            match key.as_ref() {
+                "v_m" => self.v = value,
+                "e_l" => self.e_l = value,
+                "reset_v" => self.reset_v = value,
+                "tau_m" => self.tau_m = value,
+                "i_e" => self.i_e = value,
+                "v_th" => self.v_th = value,
+                "e_e" => self.e_e = value,
+                "e_i" => self.e_i = value,
+                "tau_ge" => self.tau_ge = value,
+                "tau_gi" => self.tau_gi = value,
+                "tau_theta" => self.tau_theta = value,
+                "theta" => self.theta = value,
+                "theta_plus" => self.theta_plus = value,
+                "refact" => self.refact = value,
+                "ge" => self.ge = value,
+                "gi" => self.gi = value,
+                "fix_theta" => self.fix_theta = value,
+                _ => (),
+            }
+        }
+        Ok(())
+    }
 
     fn update(&mut self, t: Double) -> NeuronActivity {
         let mut v = self.v;
@@ -169,7 +198,7 @@ impl Neuron for Model {
         let tau_ge = self.tau_ge;
         let tau_gi = self.tau_gi;
         let tau_theta = self.tau_theta;
-        let i_e = self.i_e;
+        let i_e = self.i_e + self.i_ext;
         let dt = Network::resolution();
 
         if self.fix_theta < 0.5 {
@@ -197,6 +226,23 @@ impl Neuron for Model {
         activity
     }
 
+    fn set_input_current(&mut self, current: Double) -> Result<(), String> {
+        self.i_ext = current;
+        Ok(())
+    }
+
+    fn reset_state(&mut self, reset_thresholds: bool) {
+        self.v = self.e_l;
+        self.ge = 0.;
+        self.gi = 0.;
+        self.during_refact = false;
+        self.last_fire_t = 0.;
+        if reset_thresholds {
+            self.theta = 0.;
+        }
+        self.i_ext = 0.;
+    }
+
     fn handle_spike(&mut self, event: SpikeEvent) {
         self.add_spike(&event);
     }
@@ -233,22 +279,78 @@ impl Neuron for Model {
         records
     }
 
-    fn get_property(&self, name: String) -> Double {
+    fn property_names(&self) -> Vec<String> {
+        [
+            "v",
+            "ge",
+            "gi",
+            "theta",
+            "v_th",
+            "e_l",
+            "e_e",
+            "e_i",
+            "reset_v",
+            "tau_m",
+            "tau_ge",
+            "tau_gi",
+            "tau_theta",
+            "theta_plus",
+            "refact",
+            "i_e",
+            "fix_theta",
+            "last_fire_t",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+    }
+
+    fn get_property(&self, name: String) -> Result<Double, String> {
         match name.as_ref() {
-            "v" => self.v,
-            "v_th" => self.v_th,
-            "theta" => self.theta,
-            _ => 0.,
+            "v" => Ok(self.v),
+            "ge" => Ok(self.ge),
+            "gi" => Ok(self.gi),
+            "theta" => Ok(self.theta),
+            "v_th" => Ok(self.v_th),
+            "e_l" => Ok(self.e_l),
+            "e_e" => Ok(self.e_e),
+            "e_i" => Ok(self.e_i),
+            "reset_v" => Ok(self.reset_v),
+            "tau_m" => Ok(self.tau_m),
+            "tau_ge" => Ok(self.tau_ge),
+            "tau_gi" => Ok(self.tau_gi),
+            "tau_theta" => Ok(self.tau_theta),
+            "theta_plus" => Ok(self.theta_plus),
+            "refact" => Ok(self.refact),
+            "i_e" => Ok(self.i_e),
+            "fix_theta" => Ok(self.fix_theta),
+            "last_fire_t" => Ok(self.last_fire_t),
+            _ => Err(unknown_property(&name)),
         }
     }
 
-    fn set_property(&mut self, name: String, value: Double) {
+    fn set_property(&mut self, name: String, value: Double) -> Result<(), String> {
         match name.as_ref() {
             "v" => self.v = value,
-            "v_th" => self.v_th = value,
+            "ge" => self.ge = value,
+            "gi" => self.gi = value,
             "theta" => self.theta = value,
+            "v_th" => self.v_th = value,
+            "e_l" => self.e_l = value,
+            "e_e" => self.e_e = value,
+            "e_i" => self.e_i = value,
+            "reset_v" => self.reset_v = value,
+            "tau_m" => self.tau_m = value,
+            "tau_ge" => self.tau_ge = value,
+            "tau_gi" => self.tau_gi = value,
+            "tau_theta" => self.tau_theta = value,
+            "theta_plus" => self.theta_plus = value,
+            "refact" => self.refact = value,
+            "i_e" => self.i_e = value,
             "fix_theta" => self.fix_theta = value,
-            _ => (),
+            "last_fire_t" => self.last_fire_t = value,
+            _ => return Err(unknown_property(&name)),
         }
+        Ok(())
     }
 }
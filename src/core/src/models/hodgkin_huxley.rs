@@ -3,9 +3,9 @@
 // Released under Apache 2.0 license as described in the file LICENSE.txt.
 
 use crate::events::{Event, SpikeEvent};
-use crate::models::{Neuron, NeuronActivity};
+use crate::models::{check_params, unknown_property, Integration, Neuron, NeuronActivity};
 use crate::network::Network;
-use crate::ode::rk4;
+use crate::ode::{exponential_euler_system, rk45_system, rk4_system, DEFAULT_TOLERANCE};
 use crate::{Double, Parameters, Time};
 
 pub struct Model {
@@ -20,7 +20,11 @@ pub struct Model {
     pub m: Double,
     pub h: Double,
     pub n: Double,
+    pub integration: Integration,
+    initial: [Double; 4], // v, m, h and n restored by reset_state
+    step: Double,
     i_e: Double,
+    i_ext: Double, // current of the step injected by devices
     spikes: Double,
     nid: i64,
 }
@@ -32,20 +36,31 @@ impl Model {
         let hinit = Model::get_or_default(params, "h");
         let ninit = Model::get_or_default(params, "n");
         let i_e = Model::get_or_default(params, "i_e");
+        let g_na = Model::get_or_default(params, "g_na");
+        let g_k = Model::get_or_default(params, "g_k");
+        let g_l = Model::get_or_default(params, "g_l");
+        let cm = Model::get_or_default(params, "cm");
+        let e_na = Model::get_or_default(params, "e_na");
+        let e_k = Model::get_or_default(params, "e_k");
+        let e_l = Model::get_or_default(params, "e_l");
 
         Model {
-            g_na: 120.0,  // Sodium (Na) maximum conductances, in mS/cm^2
-            g_k: 36.0,    // Postassium (K) maximum conductances, in mS/cm^2
-            g_l: 0.3,     // Leak maximum conductances, in mS/cm^2
-            cm: 1.0,      // Membrane capacitance, in uF/cm^2
-            e_na: 50.0,   // Sodium (Na) Nernst reversal potentials, in mV
-            e_k: -77.0,   // Postassium (K) Nernst reversal potentials, in mV
-            e_l: -54.402, // Leak Nernst reversal potentials, in mV
-            v: v_m,       // Voltage
-            m: minit,     // Sodium channel Activation
-            h: hinit,     // Sodium channel Inactivation
-            n: ninit,     // Potassium channel activation
-            i_e: i_e,     // Constant input current
+            g_na,     // Sodium (Na) maximum conductances, in mS/cm^2
+            g_k,      // Postassium (K) maximum conductances, in mS/cm^2
+            g_l,      // Leak maximum conductances, in mS/cm^2
+            cm,       // Membrane capacitance, in uF/cm^2
+            e_na,     // Sodium (Na) Nernst reversal potentials, in mV
+            e_k,      // Postassium (K) Nernst reversal potentials, in mV
+            e_l,      // Leak Nernst reversal potentials, in mV
+            v: v_m,   // Voltage
+            m: minit, // Sodium channel Activation
+            h: hinit, // Sodium channel Inactivation
+            n: ninit, // Potassium channel activation
+            integration: Integration::RungeKutta4,
+            initial: [v_m, minit, hinit, ninit],
+            step: 0.,
+            i_e, // Constant input current
+            i_ext: 0.,
             spikes: 0.,
             nid: -1,
         }
@@ -85,13 +100,20 @@ impl Model {
         self.spikes += e.weight() * e.multiplicity();
     }
 
-    fn parameters() -> Parameters {
+    pub fn parameters() -> Parameters {
         let mut params = Parameters::new();
         params.insert("v_m".to_string(), -65.);
         params.insert("m".to_string(), 0.);
         params.insert("h".to_string(), 0.);
         params.insert("n".to_string(), 0.);
         params.insert("i_e".to_string(), 0.);
+        params.insert("g_na".to_string(), 120.);
+        params.insert("g_k".to_string(), 36.);
+        params.insert("g_l".to_string(), 0.3);
+        params.insert("cm".to_string(), 1.);
+        params.insert("e_na".to_string(), 50.);
+        params.insert("e_k".to_string(), -77.);
+        params.insert("e_l".to_string(), -54.402);
         params
     }
 
@@ -115,41 +137,88 @@ impl Neuron for Model {
         String::from("HodgkinHuxley")
     }
 
-    fn set_params(&mut self, _params: &Parameters) {}
+    fn set_params(&mut self, params: &Parameters) -> Result<(), String> {
+        check_params(params, &Model::parameters())?;
+        for (key, &value) in params {
+            match key.as_ref() {
+                "v_m" => {
+                    self.v = value;
+                    self.initial[0] = value;
+                }
+                "m" => {
+                    self.m = value;
+                    self.initial[1] = value;
+                }
+                "h" => {
+                    self.h = value;
+                    self.initial[2] = value;
+                }
+                "n" => {
+                    self.n = value;
+                    self.initial[3] = value;
+                }
+                "i_e" => self.i_e = value,
+                "g_na" => self.g_na = value,
+                "g_k" => self.g_k = value,
+                "g_l" => self.g_l = value,
+                "cm" => self.cm = value,
+                "e_na" => self.e_na = value,
+                "e_k" => self.e_k = value,
+                "e_l" => self.e_l = value,
+                _ => (),
+            }
+        }
+        Ok(())
+    }
 
     fn update(&mut self, t: Double) -> NeuronActivity {
-        let mut v = self.v;
-        let mut m = self.m;
-        let mut h = self.h;
-        let mut n = self.n;
         let dt = Network::resolution();
-
-        let a_m = self.alpha_m(v);
-        let a_h = self.alpha_h(v);
-        let a_n = self.alpha_n(v);
-
-        let b_m = self.beta_m(v);
-        let b_h = self.beta_h(v);
-        let b_n = self.beta_n(v);
-
-        let d_m = |y: Double| a_m * (1.0 - y) - b_m * y;
-        let d_h = |y: Double| a_h * (1.0 - y) - b_h * y;
-        let d_n = |y: Double| a_n * (1.0 - y) - b_n * y;
-
-        m += rk4(d_m, m, dt);
-        h += rk4(d_h, h, dt);
-        n += rk4(d_n, n, dt);
-
-        let i_na = self.g_na * m * m * m * h * (v - self.e_na);
-        let i_k = self.g_k * n * n * n * n * (v - self.e_k);
-        let i_l = self.g_l * (v - self.e_l);
-
-        let cm = self.cm;
-        let i_e = self.i_e;
         let i_syn = self.get_spike(t);
-        let d_v = |_y: Double| (i_syn + i_e - (i_na + i_k + i_l)) / cm;
-        v += rk4(d_v, v, dt);
+        let input = i_syn + self.i_e + self.i_ext;
+        // state vector: v, m, h, n
+        let mut y = [self.v, self.m, self.h, self.n];
+
+        match self.integration {
+            Integration::ExponentialEuler => {
+                // every variable is linear in itself once the others are fixed
+                let rates = |y: &[Double], a: &mut [Double], b: &mut [Double]| {
+                    let (v, m, h, n) = (y[0], y[1], y[2], y[3]);
+                    let g_na = self.g_na * m * m * m * h;
+                    let g_k = self.g_k * n * n * n * n;
+                    a[0] =
+                        (input + g_na * self.e_na + g_k * self.e_k + self.g_l * self.e_l) / self.cm;
+                    b[0] = (g_na + g_k + self.g_l) / self.cm;
+                    a[1] = self.alpha_m(v);
+                    b[1] = self.alpha_m(v) + self.beta_m(v);
+                    a[2] = self.alpha_h(v);
+                    b[2] = self.alpha_h(v) + self.beta_h(v);
+                    a[3] = self.alpha_n(v);
+                    b[3] = self.alpha_n(v) + self.beta_n(v);
+                };
+                exponential_euler_system(rates, &mut y, dt);
+            }
+            method => {
+                let derivatives = |y: &[Double], dydt: &mut [Double]| {
+                    let (v, m, h, n) = (y[0], y[1], y[2], y[3]);
+                    let i_na = self.g_na * m * m * m * h * (v - self.e_na);
+                    let i_k = self.g_k * n * n * n * n * (v - self.e_k);
+                    let i_l = self.g_l * (v - self.e_l);
+                    dydt[0] = (input - (i_na + i_k + i_l)) / self.cm;
+                    dydt[1] = self.alpha_m(v) * (1.0 - m) - self.beta_m(v) * m;
+                    dydt[2] = self.alpha_h(v) * (1.0 - h) - self.beta_h(v) * h;
+                    dydt[3] = self.alpha_n(v) * (1.0 - n) - self.beta_n(v) * n;
+                };
+                if method == Integration::RungeKutta45 {
+                    let mut step = self.step;
+                    rk45_system(derivatives, &mut y, dt, &mut step, DEFAULT_TOLERANCE);
+                    self.step = step;
+                } else {
+                    rk4_system(derivatives, &mut y, dt);
+                }
+            }
+        }
 
+        let v = y[0];
         let mut activity = NeuronActivity::Silent;
         if v >= 0. && self.v > v {
             // send spike
@@ -158,13 +227,28 @@ impl Neuron for Model {
         }
 
         self.v = v;
-        self.m = m;
-        self.h = h;
-        self.n = n;
+        self.m = y[1];
+        self.h = y[2];
+        self.n = y[3];
 
         activity
     }
 
+    fn set_input_current(&mut self, current: Double) -> Result<(), String> {
+        self.i_ext = current;
+        Ok(())
+    }
+
+    fn reset_state(&mut self, _reset_thresholds: bool) {
+        self.v = self.initial[0];
+        self.m = self.initial[1];
+        self.h = self.initial[2];
+        self.n = self.initial[3];
+        self.step = 0.;
+        self.spikes = 0.;
+        self.i_ext = 0.;
+    }
+
     fn handle_spike(&mut self, event: SpikeEvent) {
         self.add_spike(&event);
     }
@@ -187,15 +271,66 @@ impl Neuron for Model {
         Vec::new()
     }
 
-    fn get_property(&self, name: String) -> Double {
+    fn check_integration(&self, method: Integration) -> Result<(), String> {
+        match method {
+            Integration::Exact => Err(format!(
+                "{} does not support {} integration",
+                self.name(),
+                method
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn set_integration(&mut self, method: Integration) -> Result<(), String> {
+        self.check_integration(method)?;
+        self.integration = method;
+        Ok(())
+    }
+
+    fn property_names(&self) -> Vec<String> {
+        [
+            "v", "m", "h", "n", "i_e", "g_na", "g_k", "g_l", "cm", "e_na", "e_k", "e_l",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+    }
+
+    fn get_property(&self, name: String) -> Result<Double, String> {
         match name.as_ref() {
-            "v" => self.v,
-            "m" => self.m,
-            "h" => self.h,
-            "n" => self.n,
-            _ => 0.,
+            "v" => Ok(self.v),
+            "m" => Ok(self.m),
+            "h" => Ok(self.h),
+            "n" => Ok(self.n),
+            "i_e" => Ok(self.i_e),
+            "g_na" => Ok(self.g_na),
+            "g_k" => Ok(self.g_k),
+            "g_l" => Ok(self.g_l),
+            "cm" => Ok(self.cm),
+            "e_na" => Ok(self.e_na),
+            "e_k" => Ok(self.e_k),
+            "e_l" => Ok(self.e_l),
+            _ => Err(unknown_property(&name)),
         }
     }
 
-    fn set_property(&mut self, _name: String, _value: Double) {}
+    fn set_property(&mut self, name: String, value: Double) -> Result<(), String> {
+        match name.as_ref() {
+            "v" => self.v = value,
+            "m" => self.m = value,
+            "h" => self.h = value,
+            "n" => self.n = value,
+            "i_e" => self.i_e = value,
+            "g_na" => self.g_na = value,
+            "g_k" => self.g_k = value,
+            "g_l" => self.g_l = value,
+            "cm" => self.cm = value,
+            "e_na" => self.e_na = value,
+            "e_k" => self.e_k = value,
+            "e_l" => self.e_l = value,
+            _ => return Err(unknown_property(&name)),
+        }
+        Ok(())
+    }
 }
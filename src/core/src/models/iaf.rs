@@ -4,7 +4,7 @@
 
 // Integrate-and-fire model
 use crate::events::{Event, SpikeEvent};
-use crate::models::{Neuron, NeuronActivity};
+use crate::models::{check_params, unknown_property, Integration, Neuron, NeuronActivity};
 use crate::network::Network;
 use crate::ode::rk4;
 use crate::{Double, Index, Parameters, Time};
@@ -15,7 +15,9 @@ pub struct Model {
     pub tau_m: Double, // Membrane time constant
     pub v: Double,     // Voltage
     pub v_th: Double,  // Spike thresold
+    pub integration: Integration,
     i_e: Double,
+    i_ext: Double, // current of the step injected by devices
     spikes: Double,
     nid: Index,
 }
@@ -24,6 +26,9 @@ impl Model {
     pub fn parameters() -> Parameters {
         let mut params = Parameters::new();
         params.insert("v_m".to_string(), -65.);
+        params.insert("e_l".to_string(), -65.);
+        params.insert("r_m".to_string(), 10.);
+        params.insert("tau_m".to_string(), 10.);
         params.insert("i_e".to_string(), 0.);
         params.insert("v_th".to_string(), -55.);
         params
@@ -39,16 +44,22 @@ impl Model {
 
     pub fn new(params: &Parameters) -> Model {
         let v_m = Model::get_or_default(params, "v_m");
+        // the resting potential starts at v_m unless given
+        let e_l = params.get("e_l").cloned().unwrap_or(v_m);
+        let r_m = Model::get_or_default(params, "r_m");
+        let tau_m = Model::get_or_default(params, "tau_m");
         let i_e = Model::get_or_default(params, "i_e");
         let v_th = Model::get_or_default(params, "v_th");
 
         Model {
-            e_l: v_m,
-            r_m: 10.,
-            tau_m: 10.,
+            e_l,
+            r_m,
+            tau_m,
             v: v_m,
-            v_th: v_th,
-            i_e: i_e,
+            v_th,
+            integration: Integration::RungeKutta4,
+            i_e,
+            i_ext: 0.,
             spikes: 0.,
             nid: -1,
         }
@@ -67,9 +78,7 @@ impl Model {
 
 impl Default for Model {
     fn default() -> Model {
-        let mut params = Parameters::new();
-        params.insert("v_m".to_string(), -65.);
-        Model::new(&params)
+        Model::new(&Model::parameters())
     }
 }
 
@@ -78,7 +87,21 @@ impl Neuron for Model {
         String::from("Integrate-and-Fire")
     }
 
-    fn set_params(&mut self, _params: &Parameters) {}
+    fn set_params(&mut self, params: &Parameters) -> Result<(), String> {
+        check_params(params, &Model::parameters())?;
+        for (key, &value) in params {
+            match key.as_ref() {
+                "v_m" => self.v = value,
+                "e_l" => self.e_l = value,
+                "r_m" => self.r_m = value,
+                "tau_m" => self.tau_m = value,
+                "i_e" => self.i_e = value,
+                "v_th" => self.v_th = value,
+                _ => (),
+            }
+        }
+        Ok(())
+    }
 
     fn update(&mut self, t: Double) -> NeuronActivity {
         let mut v = self.v;
@@ -92,17 +115,54 @@ impl Neuron for Model {
 
         let tau_m = self.tau_m;
         let r_m = self.r_m;
-        let i_e = self.i_e;
+        let i_e = self.i_e + self.i_ext;
         let i_syn = self.get_spike(t);
         let dt = Network::resolution();
-        let d_v = |y: f64| (e_l - y + r_m * (i_syn + i_e)) / tau_m;
-        v += rk4(d_v, v, dt);
+        match self.integration {
+            Integration::Exact => {
+                // the input is constant over the step
+                let v_inf = e_l + r_m * (i_syn + i_e);
+                v = v_inf + (v - v_inf) * (-dt / tau_m).exp();
+            }
+            _ => {
+                let d_v = |y: f64| (e_l - y + r_m * (i_syn + i_e)) / tau_m;
+                v += rk4(d_v, v, dt);
+            }
+        }
 
         self.v = v;
 
         activity
     }
 
+    fn check_integration(&self, method: Integration) -> Result<(), String> {
+        match method {
+            Integration::RungeKutta4 | Integration::Exact => Ok(()),
+            _ => Err(format!(
+                "{} does not support {} integration",
+                self.name(),
+                method
+            )),
+        }
+    }
+
+    fn set_integration(&mut self, method: Integration) -> Result<(), String> {
+        self.check_integration(method)?;
+        self.integration = method;
+        Ok(())
+    }
+
+    fn set_input_current(&mut self, current: Double) -> Result<(), String> {
+        self.i_ext = current;
+        Ok(())
+    }
+
+    fn reset_state(&mut self, _reset_thresholds: bool) {
+        self.v = self.e_l;
+        self.spikes = 0.;
+        self.i_ext = 0.;
+    }
+
     fn handle_spike(&mut self, event: SpikeEvent) {
         self.add_spike(&event);
     }
@@ -125,13 +185,35 @@ impl Neuron for Model {
         Vec::new()
     }
 
-    fn get_property(&self, name: String) -> Double {
+    fn property_names(&self) -> Vec<String> {
+        ["v", "e_l", "r_m", "tau_m", "v_th", "i_e"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    fn get_property(&self, name: String) -> Result<Double, String> {
         match name.as_ref() {
-            "v" => self.v,
-            "v_th" => self.v_th,
-            _ => 0.,
+            "v" => Ok(self.v),
+            "e_l" => Ok(self.e_l),
+            "r_m" => Ok(self.r_m),
+            "tau_m" => Ok(self.tau_m),
+            "v_th" => Ok(self.v_th),
+            "i_e" => Ok(self.i_e),
+            _ => Err(unknown_property(&name)),
         }
     }
 
-    fn set_property(&mut self, _name: String, _value: Double) {}
+    fn set_property(&mut self, name: String, value: Double) -> Result<(), String> {
+        match name.as_ref() {
+            "v" => self.v = value,
+            "e_l" => self.e_l = value,
+            "r_m" => self.r_m = value,
+            "tau_m" => self.tau_m = value,
+            "v_th" => self.v_th = value,
+            "i_e" => self.i_e = value,
+            _ => return Err(unknown_property(&name)),
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,254 @@
+// Copyright (c) 2019 Libgirl
+//
+// Released under Apache 2.0 license as described in the file LICENSE.txt.
+
+// Poisson spike source whose rate follows a schedule over the simulation
+// time. Rates are in Hertz and times in ms.
+use crate::events::SpikeEvent;
+use crate::models::{check_params, unknown_property, Neuron, NeuronActivity};
+use crate::network::Network;
+use crate::{Double, Index, Parameters, Time};
+use std::f64::consts::PI;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "schedule", rename_all = "snake_case")]
+pub enum RateSchedule {
+    Constant {
+        rate: Double,
+    },
+    // rates[i] from times[i] on, zero before the first time
+    Steps {
+        times: Vec<Time>,
+        rates: Vec<Double>,
+    },
+    // mean + amplitude * sin(2 pi frequency t + phase), frequency in Hertz
+    // and phase in radians, cut at zero
+    Sinusoid {
+        mean: Double,
+        amplitude: Double,
+        frequency: Double,
+        #[serde(default)]
+        phase: Double,
+    },
+    // samples taken every `interval` ms from `start` on, linearly
+    // interpolated and zero outside of them
+    Sampled {
+        #[serde(default)]
+        start: Time,
+        interval: Time,
+        rates: Vec<Double>,
+    },
+}
+
+impl RateSchedule {
+    pub fn validate(&self) -> Result<(), String> {
+        let non_negative = |rates: &[Double]| {
+            if rates.iter().all(|r| r.is_finite() && *r >= 0.) {
+                Ok(())
+            } else {
+                Err("rates must be finite and non-negative".to_string())
+            }
+        };
+        match self {
+            RateSchedule::Constant { rate } => non_negative(&[*rate]),
+            RateSchedule::Steps { times, rates } => {
+                if times.len() != rates.len() {
+                    return Err(format!(
+                        "expected {} rates but {} were given",
+                        times.len(),
+                        rates.len()
+                    ));
+                }
+                if times.windows(2).any(|w| w[0] >= w[1]) {
+                    return Err("step times must be strictly increasing".to_string());
+                }
+                non_negative(rates)
+            }
+            RateSchedule::Sinusoid {
+                mean,
+                amplitude,
+                frequency,
+                phase,
+            } => {
+                if [*mean, *amplitude, *frequency, *phase]
+                    .iter()
+                    .all(|v| v.is_finite())
+                {
+                    Ok(())
+                } else {
+                    Err("sinusoid parameters must be finite".to_string())
+                }
+            }
+            RateSchedule::Sampled {
+                interval, rates, ..
+            } => {
+                if *interval <= 0. {
+                    return Err(format!("invalid sampling interval {}", interval));
+                }
+                non_negative(rates)
+            }
+        }
+    }
+
+    pub fn rate(&self, t: Time) -> Double {
+        match self {
+            RateSchedule::Constant { rate } => *rate,
+            RateSchedule::Steps { times, rates } => {
+                match times.iter().rposition(|&start| start <= t) {
+                    Some(i) => rates[i],
+                    None => 0.,
+                }
+            }
+            RateSchedule::Sinusoid {
+                mean,
+                amplitude,
+                frequency,
+                phase,
+            } => (mean + amplitude * (2. * PI * frequency * t / 1000. + phase).sin()).max(0.),
+            RateSchedule::Sampled {
+                start,
+                interval,
+                rates,
+            } => {
+                let position = (t - start) / interval;
+                if position < 0. || rates.is_empty() {
+                    return 0.;
+                }
+                let i = position.floor() as usize;
+                if i + 1 < rates.len() {
+                    let fraction = position - i as Double;
+                    rates[i] + (rates[i + 1] - rates[i]) * fraction
+                } else if i + 1 == rates.len() && position == i as Double {
+                    rates[i]
+                } else {
+                    0.
+                }
+            }
+        }
+    }
+}
+
+pub struct Model {
+    schedule: RateSchedule,
+    rate: Double, // rate of the last step
+    precise_timing: bool,
+    nid: Index,
+}
+
+impl Model {
+    pub fn parameters() -> Parameters {
+        let mut params = Parameters::new();
+        params.insert("freq".to_string(), 0.);
+        params
+    }
+
+    pub fn new(params: &Parameters) -> Model {
+        let rate = params.get("freq").cloned().unwrap_or(0.);
+        Model {
+            schedule: RateSchedule::Constant { rate },
+            rate,
+            precise_timing: false,
+            nid: -1,
+        }
+    }
+
+    pub fn schedule(&self) -> &RateSchedule {
+        &self.schedule
+    }
+}
+
+impl Default for Model {
+    fn default() -> Model {
+        Model::new(&Model::parameters())
+    }
+}
+
+impl Neuron for Model {
+    fn name(&self) -> String {
+        String::from("inhomogeneous_poisson_generator")
+    }
+
+    fn set_params(&mut self, params: &Parameters) -> Result<(), String> {
+        check_params(params, &Model::parameters())?;
+        if let Some(&freq) = params.get("freq") {
+            self.set_property("freq".to_string(), freq)?;
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, t: Double) -> NeuronActivity {
+        let resolution = Network::resolution();
+        self.rate = self.schedule.rate(t);
+        let step_freq = self.rate * resolution / 1000.;
+
+        if step_freq >= 1. || step_freq > rand::random::<f64>() {
+            let mut se = SpikeEvent::new();
+            if self.precise_timing {
+                se.set_offset(rand::random::<f64>() * resolution);
+            }
+            return NeuronActivity::Fires(se);
+        }
+        NeuronActivity::Silent
+    }
+
+    fn handle_spike(&mut self, _event: SpikeEvent) {}
+
+    fn set_neuron_id(&mut self, nid: i64) {
+        self.nid = nid;
+    }
+
+    fn neuron_id(&self) -> i64 {
+        self.nid
+    }
+
+    fn set_precise_timing(&mut self, enabled: bool) -> Result<(), String> {
+        self.precise_timing = enabled;
+        Ok(())
+    }
+
+    fn check_rate_schedule(&self, schedule: &RateSchedule) -> Result<(), String> {
+        schedule.validate()
+    }
+
+    fn set_rate_schedule(&mut self, schedule: &RateSchedule) -> Result<(), String> {
+        self.check_rate_schedule(schedule)?;
+        self.schedule = schedule.clone();
+        Ok(())
+    }
+
+    fn new_spike_record(&mut self) {}
+
+    fn set_spike_recording(&mut self, _is_on: bool) {}
+
+    fn clear_spike_records(&mut self) {}
+
+    fn get_spike_records(&self) -> Vec<Vec<Time>> {
+        Vec::new()
+    }
+
+    fn property_names(&self) -> Vec<String> {
+        ["freq"].iter().map(|s| s.to_string()).collect()
+    }
+
+    // `freq` reads the rate of the last step and replaces the schedule by a
+    // constant rate when set.
+    fn get_property(&self, name: String) -> Result<Double, String> {
+        match name.as_ref() {
+            "freq" => Ok(self.rate),
+            _ => Err(unknown_property(&name)),
+        }
+    }
+
+    fn set_property(&mut self, name: String, value: Double) -> Result<(), String> {
+        match name.as_ref() {
+            "freq" => {
+                let schedule = RateSchedule::Constant { rate: value };
+                schedule.validate()?;
+                self.schedule = schedule;
+                self.rate = value;
+            }
+            _ => return Err(unknown_property(&name)),
+        }
+        Ok(())
+    }
+}
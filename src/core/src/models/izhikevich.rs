@@ -3,9 +3,9 @@
 // Released under Apache 2.0 license as described in the file LICENSE.txt.
 
 use crate::events::{Event, SpikeEvent};
-use crate::models::{Neuron, NeuronActivity};
+use crate::models::{check_params, unknown_property, Integration, Neuron, NeuronActivity};
 use crate::network::Network;
-use crate::ode::rk4;
+use crate::ode::{rk45_system_until, rk4_system, DEFAULT_TOLERANCE};
 use crate::{Double, Parameters, Time};
 
 #[derive(Debug)]
@@ -16,8 +16,13 @@ pub struct Model {
     pub d: Double,
     pub v: Double,
     pub u: Double,
-    v_th: Double,
+    pub integration: Integration,
+    initial: [Double; 2], // v and u restored by reset_state
+    step: Double,
+    v_th: Double, // spike peak
+    i_th: Double, // threshold current, subtracted from the input
     i_e: Double,
+    i_ext: Double, // current of the step injected by devices
     spikes: Double,
     nid: i64,
 }
@@ -32,6 +37,7 @@ impl Model {
         params.insert("d".to_string(), 6.);
         params.insert("i_e".to_string(), 0.);
         params.insert("v_th".to_string(), 30.);
+        params.insert("i_th".to_string(), 0.);
         params
     }
 
@@ -51,16 +57,22 @@ impl Model {
         let d = Model::get_or_default(params, "d");
         let i_e = Model::get_or_default(params, "i_e");
         let v_th = Model::get_or_default(params, "v_th");
+        let i_th = Model::get_or_default(params, "i_th");
 
         Model {
-            a: a,
-            b: b,
-            c: c,
-            d: d,
+            a,
+            b,
+            c,
+            d,
             v: v_m,
             u: b * v_m,
-            v_th: v_th,
-            i_e: i_e,
+            integration: Integration::RungeKutta4,
+            initial: [v_m, b * v_m],
+            step: 0.,
+            v_th,
+            i_th,
+            i_e,
+            i_ext: 0.,
             spikes: 0.,
             nid: -1,
         }
@@ -79,9 +91,7 @@ impl Model {
 
 impl Default for Model {
     fn default() -> Model {
-        let mut params = Parameters::new();
-        params.insert("v_m".to_string(), -70.);
-        Model::new(&params)
+        Model::new(&Model::parameters())
     }
 }
 
@@ -90,22 +100,60 @@ impl Neuron for Model {
         String::from("Izhikevich")
     }
 
-    fn set_params(&mut self, _params: &Parameters) {}
+    fn set_params(&mut self, params: &Parameters) -> Result<(), String> {
+        check_params(params, &Model::parameters())?;
+        for (key, &value) in params {
+            match key.as_ref() {
+                "v_m" => self.v = value,
+                "a" => self.a = value,
+                "b" => self.b = value,
+                "c" => self.c = value,
+                "d" => self.d = value,
+                "i_e" => self.i_e = value,
+                "v_th" => self.v_th = value,
+                "i_th" => self.i_th = value,
+                _ => (),
+            }
+        }
+        // u starts at b * v_m, as in new
+        if let Some(&v_m) = params.get("v_m") {
+            self.initial[0] = v_m;
+        }
+        if params.contains_key("v_m") || params.contains_key("b") {
+            self.initial[1] = self.b * self.initial[0];
+        }
+        Ok(())
+    }
 
     fn update(&mut self, t: Double) -> NeuronActivity {
-        let mut v = self.v;
-        let mut u = self.u;
         let a = self.a;
         let b = self.b;
-        let i_e = self.i_e;
-        let i_syn = self.get_spike(t);
+        let input = self.get_spike(t) + self.i_e + self.i_ext - self.i_th;
+        let v_th = self.v_th;
         let dt = Network::resolution();
 
-        let d_v = move |y: Double| 0.04 * y * y + 5.0 * y + 140. - b * u + i_syn + i_e;
-        v += rk4(d_v, v, dt);
-
-        let d_u = move |y: Double| a * (v - y);
-        u += rk4(d_u, u, dt);
+        let mut y = [self.v, self.u];
+        let derivatives = move |y: &[Double], dydt: &mut [Double]| {
+            let (v, u) = (y[0], y[1]);
+            dydt[0] = 0.04 * v * v + 5.0 * v + 140. - b * u + input;
+            dydt[1] = a * (v - u);
+        };
+        if self.integration == Integration::RungeKutta45 {
+            // the quadratic term diverges in finite time past the peak, so
+            // the step ends there
+            rk45_system_until(
+                derivatives,
+                &mut y,
+                dt,
+                &mut self.step,
+                DEFAULT_TOLERANCE,
+                |y| y[0] > v_th,
+            );
+        } else {
+            rk4_system(derivatives, &mut y, dt);
+        }
+        let mut v = y[0];
+        let mut u = y[1];
 
         let mut activity = NeuronActivity::Silent;
         if v > self.v_th {
@@ -121,6 +169,19 @@ impl Neuron for Model {
         activity
     }
 
+    fn set_input_current(&mut self, current: Double) -> Result<(), String> {
+        self.i_ext = current;
+        Ok(())
+    }
+
+    fn reset_state(&mut self, _reset_thresholds: bool) {
+        self.v = self.initial[0];
+        self.u = self.initial[1];
+        self.step = 0.;
+        self.spikes = 0.;
+        self.i_ext = 0.;
+    }
+
     fn handle_spike(&mut self, event: SpikeEvent) {
         self.add_spike(&event);
     }
@@ -143,13 +204,64 @@ impl Neuron for Model {
         Vec::new()
     }
 
-    fn get_property(&self, name: String) -> Double {
+    fn check_integration(&self, method: Integration) -> Result<(), String> {
+        match method {
+            Integration::RungeKutta4 | Integration::RungeKutta45 => Ok(()),
+            _ => Err(format!(
+                "{} does not support {} integration",
+                self.name(),
+                method
+            )),
+        }
+    }
+
+    fn set_integration(&mut self, method: Integration) -> Result<(), String> {
+        self.check_integration(method)?;
+        self.integration = method;
+        Ok(())
+    }
+
+    // v_th is the spike peak rather than a threshold: raising the threshold
+    // current delays spikes instead.
+    fn threshold_property(&self) -> Option<String> {
+        Some("i_th".to_string())
+    }
+
+    fn property_names(&self) -> Vec<String> {
+        ["v", "u", "a", "b", "c", "d", "v_th", "i_th", "i_e"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    fn get_property(&self, name: String) -> Result<Double, String> {
         match name.as_ref() {
-            "v" => self.v,
-            "v_th" => self.v_th,
-            _ => 0.,
+            "v" => Ok(self.v),
+            "u" => Ok(self.u),
+            "a" => Ok(self.a),
+            "b" => Ok(self.b),
+            "c" => Ok(self.c),
+            "d" => Ok(self.d),
+            "v_th" => Ok(self.v_th),
+            "i_th" => Ok(self.i_th),
+            "i_e" => Ok(self.i_e),
+            _ => Err(unknown_property(&name)),
         }
     }
 
-    fn set_property(&mut self, _name: String, _value: Double) {}
+    fn set_property(&mut self, name: String, value: Double) -> Result<(), String> {
+        match name.as_ref() {
+            "v" => self.v = value,
+            "u" => self.u = value,
+            "a" => self.a = value,
+            "b" => self.b = value,
+            "c" => self.c = value,
+            "d" => self.d = value,
+            "v_th" => self.v_th = value,
+            "i_th" => self.i_th = value,
+            "i_e" => self.i_e = value,
+            _ => return Err(unknown_property(&name)),
+        }
+        Ok(())
+    }
 }
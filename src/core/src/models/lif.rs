@@ -0,0 +1,812 @@
+// Copyright (c) 2019 Libgirl
+//
+// Released under Apache 2.0 license as described in the file LICENSE.txt.
+
+// Leaky integrate-and-fire models following the NEST definitions of
+// iaf_psc_delta, iaf_psc_exp, iaf_psc_alpha, iaf_cond_exp and iaf_cond_alpha.
+// Units: mV, ms, pF, nS and pA (mV for iaf_psc_delta weights). Positive
+// weights are excitatory, negative weights inhibitory.
+use crate::events::{Event, SpikeEvent};
+use crate::groups::NeuronGroup;
+use crate::models::{check_params, unknown_property, Integration, Neuron, NeuronActivity};
+use crate::network::Network;
+use crate::ode::{p21, p31, rk4};
+use crate::{Double, Index, Parameters, Time};
+use std::f64::consts::E;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Synapse {
+    Delta,            // iaf_psc_delta: jump of the membrane potential
+    CurrentExp,       // iaf_psc_exp: exponentially decaying current
+    CurrentAlpha,     // iaf_psc_alpha: alpha-shaped current
+    ConductanceExp,   // iaf_cond_exp: exponentially decaying conductance
+    ConductanceAlpha, // iaf_cond_alpha: alpha-shaped conductance
+}
+
+impl Synapse {
+    pub fn name(self) -> &'static str {
+        match self {
+            Synapse::Delta => "iaf_psc_delta",
+            Synapse::CurrentExp => "iaf_psc_exp",
+            Synapse::CurrentAlpha => "iaf_psc_alpha",
+            Synapse::ConductanceExp => "iaf_cond_exp",
+            Synapse::ConductanceAlpha => "iaf_cond_alpha",
+        }
+    }
+
+    fn is_conductance(self) -> bool {
+        self == Synapse::ConductanceExp || self == Synapse::ConductanceAlpha
+    }
+
+    fn is_alpha(self) -> bool {
+        self == Synapse::CurrentAlpha || self == Synapse::ConductanceAlpha
+    }
+}
+
+#[derive(Debug)]
+pub struct Model {
+    pub c_m: Double,        // Membrane capacitance
+    pub tau_m: Double,      // Membrane time constant, c_m / g_l
+    pub e_l: Double,        // Resting potential
+    pub v_th: Double,       // Spike threshold
+    pub v_reset: Double,    // Reset potential
+    pub t_ref: Double,      // Refractory period
+    pub e_ex: Double,       // Excitatory reversal potential
+    pub e_in: Double,       // Inhibitory reversal potential
+    pub tau_syn_ex: Double, // Excitatory synaptic time constant
+    pub tau_syn_in: Double, // Inhibitory synaptic time constant
+    pub v: Double,          // Voltage
+    pub syn_ex: Double,     // Excitatory current or conductance
+    pub syn_in: Double,     // Inhibitory current or conductance
+    pub synapse: Synapse,
+    pub integration: Integration,
+    precise_timing: bool,
+    dsyn_ex: Double, // derivatives of the alpha-shaped inputs
+    dsyn_in: Double,
+    delta_v: Double,                     // pending jumps of iaf_psc_delta
+    timed_inputs: Vec<(Double, Double)>, // offsets and weights of precisely timed spikes
+    refractory_left: Double,
+    i_e: Double,
+    i_ext: Double, // current of the step injected by devices
+    nid: Index,
+    is_record_spikes: bool,
+    spike_records: Vec<Vec<Time>>,
+}
+
+impl Model {
+    pub fn parameters(synapse: Synapse) -> Parameters {
+        let mut params = Parameters::new();
+        params.insert("v_m".to_string(), -70.);
+        params.insert("c_m".to_string(), 250.);
+        params.insert("e_l".to_string(), -70.);
+        params.insert("v_th".to_string(), -55.);
+        params.insert("t_ref".to_string(), 2.);
+        params.insert("i_e".to_string(), 0.);
+        if synapse.is_conductance() {
+            params.insert("g_l".to_string(), 16.6667);
+            params.insert("v_reset".to_string(), -60.);
+            params.insert("e_ex".to_string(), 0.);
+            params.insert("e_in".to_string(), -85.);
+            params.insert("tau_syn_ex".to_string(), 0.2);
+            params.insert("tau_syn_in".to_string(), 2.);
+        } else {
+            params.insert("tau_m".to_string(), 10.);
+            params.insert("v_reset".to_string(), -70.);
+            if synapse != Synapse::Delta {
+                params.insert("tau_syn_ex".to_string(), 2.);
+                params.insert("tau_syn_in".to_string(), 2.);
+            }
+        }
+        params
+    }
+
+    pub fn new(params: &Parameters, synapse: Synapse) -> Model {
+        let mut model = Model {
+            c_m: 0.,
+            tau_m: 0.,
+            e_l: 0.,
+            v_th: 0.,
+            v_reset: 0.,
+            t_ref: 0.,
+            e_ex: 0.,
+            e_in: 0.,
+            tau_syn_ex: 0.,
+            tau_syn_in: 0.,
+            v: 0.,
+            syn_ex: 0.,
+            syn_in: 0.,
+            synapse,
+            // NEST integrates the current-based models exactly
+            integration: if synapse.is_conductance() {
+                Integration::RungeKutta4
+            } else {
+                Integration::Exact
+            },
+            precise_timing: false,
+            dsyn_ex: 0.,
+            dsyn_in: 0.,
+            delta_v: 0.,
+            timed_inputs: Vec::new(),
+            refractory_left: 0.,
+            i_e: 0.,
+            i_ext: 0.,
+            nid: -1,
+            is_record_spikes: false,
+            spike_records: Vec::new(),
+        };
+        let mut all = Model::parameters(synapse);
+        for (key, value) in params {
+            all.insert(key.clone(), *value);
+        }
+        // the membrane time constant is derived from g_l in the
+        // conductance-based models, so c_m has to be set first
+        model.c_m = all["c_m"];
+        for (key, &value) in &all {
+            model.set_param(key, value);
+        }
+        model
+    }
+
+    fn set_param(&mut self, key: &str, value: Double) {
+        match key {
+            "v_m" => self.v = value,
+            k => {
+                let _ = self.set_property(k.to_string(), value);
+            }
+        }
+    }
+
+    pub fn g_l(&self) -> Double {
+        self.c_m / self.tau_m
+    }
+
+    fn add_spike(&mut self, e: &SpikeEvent) {
+        let w = e.weight() * e.multiplicity();
+        if self.is_precise() {
+            self.timed_inputs.push((e.offset(), w));
+        } else {
+            self.add_input(w);
+        }
+    }
+
+    fn add_input(&mut self, w: Double) {
+        match self.synapse {
+            Synapse::Delta => {
+                if self.refractory_left <= 0. {
+                    self.delta_v += w;
+                }
+            }
+            // normalized so that the input peaks at |w| after tau_syn
+            Synapse::CurrentAlpha | Synapse::ConductanceAlpha => {
+                if w > 0. {
+                    self.dsyn_ex += E / self.tau_syn_ex * w;
+                } else {
+                    self.dsyn_in -= E / self.tau_syn_in * w;
+                }
+            }
+            Synapse::CurrentExp | Synapse::ConductanceExp => {
+                if w > 0. {
+                    self.syn_ex += w;
+                } else {
+                    self.syn_in -= w;
+                }
+            }
+        }
+    }
+
+    // Exact propagation of the membrane potential over one step, given the
+    // synaptic state at the beginning of the step.
+    fn propagate_v(&self, dt: Double) -> Double {
+        let tau_m = self.tau_m;
+        let c_m = self.c_m;
+        let p22 = (-dt / tau_m).exp();
+        let mut v = self.e_l
+            + (self.v - self.e_l) * p22
+            + (self.i_e + self.i_ext) * tau_m / c_m * (1. - p22);
+        match self.synapse {
+            Synapse::CurrentExp => {
+                v += (p21(tau_m, self.tau_syn_ex, dt) * self.syn_ex
+                    - p21(tau_m, self.tau_syn_in, dt) * self.syn_in)
+                    / c_m;
+            }
+            Synapse::CurrentAlpha => {
+                v += (p21(tau_m, self.tau_syn_ex, dt) * self.syn_ex
+                    + p31(tau_m, self.tau_syn_ex, dt) * self.dsyn_ex
+                    - p21(tau_m, self.tau_syn_in, dt) * self.syn_in
+                    - p31(tau_m, self.tau_syn_in, dt) * self.dsyn_in)
+                    / c_m;
+            }
+            _ => (),
+        }
+        v
+    }
+
+    // Advances the membrane potential over one step, given the synaptic
+    // state at the beginning of the step.
+    fn integrate(&mut self, dt: Double) {
+        if self.integration == Integration::Exact {
+            self.v = self.propagate_v(dt) + self.delta_v;
+            return;
+        }
+        let e_l = self.e_l;
+        let tau_m = self.tau_m;
+        let c_m = self.c_m;
+        let i_e = self.i_e + self.i_ext;
+        let synapse_is_conductance = self.synapse.is_conductance();
+        let (syn_ex, syn_in, e_ex, e_in) = (self.syn_ex, self.syn_in, self.e_ex, self.e_in);
+        let d_v = |y: Double| {
+            let i_syn = if synapse_is_conductance {
+                syn_ex * (e_ex - y) + syn_in * (e_in - y)
+            } else {
+                syn_ex - syn_in
+            };
+            (e_l - y) / tau_m + (i_syn + i_e) / c_m
+        };
+        self.v += rk4(d_v, self.v, dt) + self.delta_v;
+    }
+
+    // Spikes are timed within the step only when the subthreshold dynamics
+    // are propagated exactly, so that the step can be split at any offset.
+    fn is_precise(&self) -> bool {
+        self.integration == Integration::Exact && self.precise_timing
+    }
+
+    // Integrates one step piecewise between the offsets of the timed inputs
+    // and returns the offset of the threshold crossing, found by linear
+    // interpolation. The membrane potential is held while refractory and
+    // after the crossing.
+    fn update_precise(&mut self, dt: Double, refractory: bool) -> Option<Double> {
+        let mut inputs = std::mem::take(&mut self.timed_inputs);
+        inputs.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        inputs.push((dt, 0.));
+        let mut crossing = None;
+        let mut elapsed = 0.;
+        for (offset, w) in inputs {
+            let h = offset.min(dt).max(elapsed) - elapsed;
+            if !refractory && crossing.is_none() {
+                let v_start = self.v;
+                self.v = self.propagate_v(h);
+                if self.v >= self.v_th {
+                    let fraction = if self.v > v_start {
+                        ((self.v_th - v_start) / (self.v - v_start)).max(0.)
+                    } else {
+                        0.
+                    };
+                    crossing = Some(elapsed + h * fraction);
+                }
+            }
+            self.decay_synapses(h);
+            elapsed += h;
+
+            if self.synapse == Synapse::Delta {
+                if !refractory && crossing.is_none() {
+                    self.v += w;
+                    if self.v >= self.v_th {
+                        crossing = Some(elapsed);
+                    }
+                }
+            } else {
+                self.add_input(w);
+            }
+        }
+        crossing
+    }
+
+    // Exact propagation of the synaptic kernels over one step.
+    fn decay_synapses(&mut self, dt: Double) {
+        let p_ex = (-dt / self.tau_syn_ex).exp();
+        let p_in = (-dt / self.tau_syn_in).exp();
+        if self.synapse.is_alpha() {
+            self.syn_ex = p_ex * (self.syn_ex + dt * self.dsyn_ex);
+            self.dsyn_ex *= p_ex;
+            self.syn_in = p_in * (self.syn_in + dt * self.dsyn_in);
+            self.dsyn_in *= p_in;
+        } else if self.synapse != Synapse::Delta {
+            self.syn_ex *= p_ex;
+            self.syn_in *= p_in;
+        }
+    }
+}
+
+impl Default for Model {
+    fn default() -> Model {
+        Model::new(&Parameters::new(), Synapse::CurrentExp)
+    }
+}
+
+impl Neuron for Model {
+    fn name(&self) -> String {
+        self.synapse.name().to_string()
+    }
+
+    fn set_params(&mut self, params: &Parameters) -> Result<(), String> {
+        check_params(params, &Model::parameters(self.synapse))?;
+        if let Some(&c_m) = params.get("c_m") {
+            // keep g_l of the conductance-based models unchanged
+            if self.synapse.is_conductance() {
+                self.tau_m *= c_m / self.c_m;
+            }
+            self.c_m = c_m;
+        }
+        for (key, &value) in params {
+            if key != "c_m" {
+                self.set_param(key, value);
+            }
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, t: Double) -> NeuronActivity {
+        let dt = Network::resolution();
+
+        let refractory = self.refractory_left > 0.;
+        if refractory {
+            self.refractory_left -= dt;
+            self.v = self.v_reset;
+        }
+        let crossing = if self.is_precise() {
+            self.update_precise(dt, refractory)
+        } else {
+            // inputs timed before precise timing was turned off
+            for (_, w) in std::mem::take(&mut self.timed_inputs) {
+                self.add_input(w);
+            }
+            if !refractory {
+                self.integrate(dt);
+            }
+            self.delta_v = 0.;
+            self.decay_synapses(dt);
+            if self.v >= self.v_th {
+                Some(0.)
+            } else {
+                None
+            }
+        };
+
+        let mut activity = NeuronActivity::Silent;
+        if let Some(offset) = crossing {
+            let mut se = SpikeEvent::new();
+            se.set_offset(offset);
+            activity = NeuronActivity::Fires(se);
+            self.v = self.v_reset;
+            self.refractory_left = self.t_ref;
+            if self.is_record_spikes {
+                let spike_record_index = self.spike_records.len() - 1;
+                self.spike_records[spike_record_index].push(t + offset);
+            }
+        }
+
+        activity
+    }
+
+    fn check_integration(&self, method: Integration) -> Result<(), String> {
+        match method {
+            Integration::Exact if self.synapse.is_conductance() => Err(format!(
+                "{} is not linear and cannot be integrated exactly",
+                self.name()
+            )),
+            Integration::RungeKutta4 if self.precise_timing => Err(format!(
+                "{} times its spikes within a step only with Exact integration",
+                self.name()
+            )),
+            Integration::RungeKutta4 | Integration::Exact => Ok(()),
+            _ => Err(format!(
+                "{} does not support {} integration",
+                self.name(),
+                method
+            )),
+        }
+    }
+
+    fn set_integration(&mut self, method: Integration) -> Result<(), String> {
+        self.check_integration(method)?;
+        self.integration = method;
+        Ok(())
+    }
+
+    // Spikes are timed within the step by the exact integration only.
+    fn set_precise_timing(&mut self, enabled: bool) -> Result<(), String> {
+        if enabled && self.integration != Integration::Exact {
+            return Err(format!(
+                "{} times its spikes within a step only with Exact integration",
+                self.name()
+            ));
+        }
+        self.precise_timing = enabled;
+        Ok(())
+    }
+
+    fn set_input_current(&mut self, current: Double) -> Result<(), String> {
+        self.i_ext = current;
+        Ok(())
+    }
+
+    fn reset_state(&mut self, _reset_thresholds: bool) {
+        self.v = self.e_l;
+        self.syn_ex = 0.;
+        self.syn_in = 0.;
+        self.dsyn_ex = 0.;
+        self.dsyn_in = 0.;
+        self.delta_v = 0.;
+        self.timed_inputs.clear();
+        self.refractory_left = 0.;
+        self.i_ext = 0.;
+    }
+
+    // Without input the membrane potential stays below the larger of its
+    // current value and the steady state of i_e, plus the excitatory charge
+    // still to flow in, which the leak can only reduce.
+    fn quiet_steps(&self, _t: Time) -> Option<usize> {
+        if self.integration != Integration::Exact
+            || self.precise_timing
+            || self.i_ext != 0.
+            || self.delta_v != 0.
+            || !self.timed_inputs.is_empty()
+        {
+            return Some(0);
+        }
+        let dt = Network::resolution();
+        let refractory_steps = (self.refractory_left.max(0.) / dt).floor() as usize;
+        let v = if self.refractory_left > 0. {
+            self.v_reset
+        } else {
+            self.v
+        };
+        let v_inf = self.e_l + self.i_e * self.tau_m / self.c_m;
+        let charge = match self.synapse {
+            Synapse::CurrentExp => self.syn_ex * self.tau_syn_ex,
+            Synapse::CurrentAlpha => {
+                (self.syn_ex + self.dsyn_ex * self.tau_syn_ex) * self.tau_syn_ex
+            }
+            _ => 0.,
+        };
+        if v.max(v_inf) + charge.max(0.) / self.c_m < self.v_th {
+            return None;
+        }
+        let synapses_at_rest =
+            self.syn_ex == 0. && self.syn_in == 0. && self.dsyn_ex == 0. && self.dsyn_in == 0.;
+        if synapses_at_rest && v < self.v_th {
+            // relaxation toward v_inf crosses the threshold at t_cross
+            let t_cross = self.tau_m * ((v_inf - v) / (v_inf - self.v_th)).ln();
+            let steps = (t_cross / dt).floor() as usize;
+            return Some(refractory_steps.saturating_add(steps.saturating_sub(1)));
+        }
+        Some(refractory_steps.saturating_sub(1))
+    }
+
+    fn skip(&mut self, steps: usize) {
+        let dt = Network::resolution();
+        let mut steps = steps;
+        while steps > 0 && self.refractory_left > 0. {
+            self.refractory_left -= dt;
+            self.v = self.v_reset;
+            self.decay_synapses(dt);
+            steps -= 1;
+        }
+        if steps > 0 {
+            let h = steps as Double * dt;
+            self.v = self.propagate_v(h);
+            self.decay_synapses(h);
+        }
+    }
+
+    fn handle_spike(&mut self, event: SpikeEvent) {
+        self.add_spike(&event);
+    }
+
+    fn set_neuron_id(&mut self, nid: i64) {
+        self.nid = nid;
+    }
+
+    fn neuron_id(&self) -> i64 {
+        self.nid
+    }
+
+    fn new_spike_record(&mut self) {
+        self.spike_records.push(Vec::new());
+    }
+
+    fn set_spike_recording(&mut self, is_on: bool) {
+        self.is_record_spikes = is_on;
+    }
+
+    fn clear_spike_records(&mut self) {
+        self.spike_records.clear();
+    }
+
+    fn get_spike_records(&self) -> Vec<Vec<Time>> {
+        self.spike_records.clone()
+    }
+
+    fn property_names(&self) -> Vec<String> {
+        let mut names = vec![
+            "v", "c_m", "tau_m", "e_l", "v_th", "v_reset", "t_ref", "i_e",
+        ];
+        if self.synapse != Synapse::Delta {
+            names.extend(&["syn_ex", "syn_in", "tau_syn_ex", "tau_syn_in"]);
+        }
+        if self.synapse.is_conductance() {
+            names.extend(&["g_l", "e_ex", "e_in"]);
+        }
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn get_property(&self, name: String) -> Result<Double, String> {
+        if !self.property_names().contains(&name) {
+            return Err(unknown_property(&name));
+        }
+        match name.as_ref() {
+            "v" => Ok(self.v),
+            "c_m" => Ok(self.c_m),
+            "tau_m" => Ok(self.tau_m),
+            "e_l" => Ok(self.e_l),
+            "v_th" => Ok(self.v_th),
+            "v_reset" => Ok(self.v_reset),
+            "t_ref" => Ok(self.t_ref),
+            "i_e" => Ok(self.i_e),
+            "syn_ex" => Ok(self.syn_ex),
+            "syn_in" => Ok(self.syn_in),
+            "tau_syn_ex" => Ok(self.tau_syn_ex),
+            "tau_syn_in" => Ok(self.tau_syn_in),
+            "g_l" => Ok(self.g_l()),
+            "e_ex" => Ok(self.e_ex),
+            "e_in" => Ok(self.e_in),
+            _ => Err(unknown_property(&name)),
+        }
+    }
+
+    fn set_property(&mut self, name: String, value: Double) -> Result<(), String> {
+        if !self.property_names().contains(&name) {
+            return Err(unknown_property(&name));
+        }
+        match name.as_ref() {
+            "v" => self.v = value,
+            "c_m" => self.c_m = value,
+            "tau_m" => self.tau_m = value,
+            "e_l" => self.e_l = value,
+            "v_th" => self.v_th = value,
+            "v_reset" => self.v_reset = value,
+            "t_ref" => self.t_ref = value,
+            "i_e" => self.i_e = value,
+            "syn_ex" => self.syn_ex = value,
+            "syn_in" => self.syn_in = value,
+            "tau_syn_ex" => self.tau_syn_ex = value,
+            "tau_syn_in" => self.tau_syn_in = value,
+            "g_l" => self.tau_m = self.c_m / value,
+            "e_ex" => self.e_ex = value,
+            "e_in" => self.e_in = value,
+            _ => return Err(unknown_property(&name)),
+        }
+        Ok(())
+    }
+}
+
+// Current-based LIF neurons integrated exactly, with the state and the
+// parameters of each neuron in arrays. The time constants and the
+// capacitance, which make the propagators, are shared by the group. Inputs
+// take effect at the beginning of the next step, as without precise timing.
+pub struct Group {
+    synapse: Synapse,
+    c_m: Double,
+    tau_m: Double,
+    tau_syn_ex: Double,
+    tau_syn_in: Double,
+    e_l: Vec<Double>,
+    v_th: Vec<Double>,
+    v_reset: Vec<Double>,
+    t_ref: Vec<Double>,
+    i_e: Vec<Double>,
+    v: Vec<Double>,
+    syn_ex: Vec<Double>,
+    syn_in: Vec<Double>,
+    dsyn_ex: Vec<Double>,
+    dsyn_in: Vec<Double>,
+    delta_v: Vec<Double>,
+    refractory_left: Vec<Double>,
+}
+
+const SHARED_PROPERTIES: [&str; 4] = ["c_m", "tau_m", "tau_syn_ex", "tau_syn_in"];
+
+impl Group {
+    pub fn new(size: usize, params: &Parameters, synapse: Synapse) -> Result<Group, String> {
+        if synapse.is_conductance() {
+            return Err(format!("{} has no group implementation", synapse.name()));
+        }
+        check_params(params, &Model::parameters(synapse))?;
+        // the defaults of the single neuron model
+        let model = Model::new(params, synapse);
+        Ok(Group {
+            synapse,
+            c_m: model.c_m,
+            tau_m: model.tau_m,
+            tau_syn_ex: model.tau_syn_ex,
+            tau_syn_in: model.tau_syn_in,
+            e_l: vec![model.e_l; size],
+            v_th: vec![model.v_th; size],
+            v_reset: vec![model.v_reset; size],
+            t_ref: vec![model.t_ref; size],
+            i_e: vec![model.i_e; size],
+            v: vec![model.v; size],
+            syn_ex: vec![0.; size],
+            syn_in: vec![0.; size],
+            dsyn_ex: vec![0.; size],
+            dsyn_in: vec![0.; size],
+            delta_v: vec![0.; size],
+            refractory_left: vec![0.; size],
+        })
+    }
+
+    fn property_mut(&mut self, name: &str) -> Option<&mut Vec<Double>> {
+        match name {
+            "v" => Some(&mut self.v),
+            "e_l" => Some(&mut self.e_l),
+            "v_th" => Some(&mut self.v_th),
+            "v_reset" => Some(&mut self.v_reset),
+            "t_ref" => Some(&mut self.t_ref),
+            "i_e" => Some(&mut self.i_e),
+            "syn_ex" => Some(&mut self.syn_ex),
+            "syn_in" => Some(&mut self.syn_in),
+            _ => None,
+        }
+    }
+}
+
+impl NeuronGroup for Group {
+    fn name(&self) -> String {
+        self.synapse.name().to_string()
+    }
+
+    fn len(&self) -> usize {
+        self.v.len()
+    }
+
+    // Same arithmetic as `Model::update` with exact integration, the unused
+    // synaptic variables staying at zero.
+    fn update_group(&mut self, _t: Time, fired: &mut Vec<usize>) {
+        let dt = Network::resolution();
+        let (tau_m, c_m) = (self.tau_m, self.c_m);
+        let p22 = (-dt / tau_m).exp();
+        // iaf_psc_delta has no synaptic time constants
+        let (p21_ex, p21_in, p31_ex, p31_in) = if self.synapse == Synapse::Delta {
+            (0., 0., 0., 0.)
+        } else {
+            (
+                p21(tau_m, self.tau_syn_ex, dt),
+                p21(tau_m, self.tau_syn_in, dt),
+                p31(tau_m, self.tau_syn_ex, dt),
+                p31(tau_m, self.tau_syn_in, dt),
+            )
+        };
+        for i in 0..self.v.len() {
+            if self.refractory_left[i] > 0. {
+                self.refractory_left[i] -= dt;
+                self.v[i] = self.v_reset[i];
+            } else {
+                let mut v = self.e_l[i]
+                    + (self.v[i] - self.e_l[i]) * p22
+                    + self.i_e[i] * tau_m / c_m * (1. - p22);
+                v += (p21_ex * self.syn_ex[i] + p31_ex * self.dsyn_ex[i]
+                    - p21_in * self.syn_in[i]
+                    - p31_in * self.dsyn_in[i])
+                    / c_m;
+                self.v[i] = v + self.delta_v[i];
+            }
+        }
+        for d in self.delta_v.iter_mut() {
+            *d = 0.;
+        }
+        let p_ex = (-dt / self.tau_syn_ex).exp();
+        let p_in = (-dt / self.tau_syn_in).exp();
+        for (syn, dsyn) in self.syn_ex.iter_mut().zip(self.dsyn_ex.iter_mut()) {
+            *syn = p_ex * (*syn + dt * *dsyn);
+            *dsyn *= p_ex;
+        }
+        for (syn, dsyn) in self.syn_in.iter_mut().zip(self.dsyn_in.iter_mut()) {
+            *syn = p_in * (*syn + dt * *dsyn);
+            *dsyn *= p_in;
+        }
+        for i in 0..self.v.len() {
+            if self.v[i] >= self.v_th[i] {
+                self.v[i] = self.v_reset[i];
+                self.refractory_left[i] = self.t_ref[i];
+                fired.push(i);
+            }
+        }
+    }
+
+    fn handle_spike(&mut self, i: usize, event: SpikeEvent) {
+        let w = event.weight() * event.multiplicity();
+        match self.synapse {
+            Synapse::Delta => {
+                if self.refractory_left[i] <= 0. {
+                    self.delta_v[i] += w;
+                }
+            }
+            Synapse::CurrentAlpha => {
+                if w > 0. {
+                    self.dsyn_ex[i] += E / self.tau_syn_ex * w;
+                } else {
+                    self.dsyn_in[i] -= E / self.tau_syn_in * w;
+                }
+            }
+            _ => {
+                if w > 0. {
+                    self.syn_ex[i] += w;
+                } else {
+                    self.syn_in[i] -= w;
+                }
+            }
+        }
+    }
+
+    fn reset_state(&mut self) {
+        self.v.copy_from_slice(&self.e_l);
+        for state in [
+            &mut self.syn_ex,
+            &mut self.syn_in,
+            &mut self.dsyn_ex,
+            &mut self.dsyn_in,
+            &mut self.delta_v,
+            &mut self.refractory_left,
+        ]
+        .iter_mut()
+        {
+            for x in state.iter_mut() {
+                *x = 0.;
+            }
+        }
+    }
+
+    fn property_names(&self) -> Vec<String> {
+        let mut names = vec![
+            "v", "c_m", "tau_m", "e_l", "v_th", "v_reset", "t_ref", "i_e",
+        ];
+        if self.synapse != Synapse::Delta {
+            names.extend(&["syn_ex", "syn_in", "tau_syn_ex", "tau_syn_in"]);
+        }
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn get_property(&self, i: usize, name: String) -> Result<Double, String> {
+        if !self.property_names().contains(&name) {
+            return Err(unknown_property(&name));
+        }
+        match name.as_ref() {
+            "v" => Ok(self.v[i]),
+            "c_m" => Ok(self.c_m),
+            "tau_m" => Ok(self.tau_m),
+            "e_l" => Ok(self.e_l[i]),
+            "v_th" => Ok(self.v_th[i]),
+            "v_reset" => Ok(self.v_reset[i]),
+            "t_ref" => Ok(self.t_ref[i]),
+            "i_e" => Ok(self.i_e[i]),
+            "syn_ex" => Ok(self.syn_ex[i]),
+            "syn_in" => Ok(self.syn_in[i]),
+            "tau_syn_ex" => Ok(self.tau_syn_ex),
+            "tau_syn_in" => Ok(self.tau_syn_in),
+            _ => Err(unknown_property(&name)),
+        }
+    }
+
+    fn set_property(&mut self, i: usize, name: String, value: Double) -> Result<(), String> {
+        if !self.property_names().contains(&name) {
+            return Err(unknown_property(&name));
+        }
+        if SHARED_PROPERTIES.contains(&name.as_ref()) {
+            if self.get_property(i, name.clone())? == value {
+                return Ok(());
+            }
+            return Err(format!(
+                "{} is shared by the neurons of a group and is set when creating it",
+                name
+            ));
+        }
+        match self.property_mut(&name) {
+            Some(values) => {
+                values[i] = value;
+                Ok(())
+            }
+            None => Err(unknown_property(&name)),
+        }
+    }
+}
@@ -0,0 +1,153 @@
+// Copyright (c) 2019 Libgirl
+//
+// Released under Apache 2.0 license as described in the file LICENSE.txt.
+
+// Neuron models available by name, so that models defined outside this crate
+// can be created through `Network::create_model` and the FFI.
+use crate::models::{
+    adex, cb_ath_lif, check_params, hodgkin_huxley, iaf, inhomogeneous_poisson, izhikevich, lif,
+    spike_generator, static_poisson, Neuron,
+};
+use crate::Parameters;
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+pub type NeuronFactory = fn(&Parameters) -> Box<dyn Neuron>;
+
+#[derive(Clone)]
+struct Entry {
+    factory: NeuronFactory,
+    parameters: Parameters,
+}
+
+lazy_static! {
+    static ref MODELS: RwLock<HashMap<String, Entry>> = RwLock::new(builtin_models());
+}
+
+fn builtin_models() -> HashMap<String, Entry> {
+    let mut models = HashMap::new();
+    let mut insert = |name: &str, factory: NeuronFactory, parameters: Parameters| {
+        models.insert(
+            name.to_string(),
+            Entry {
+                factory,
+                parameters,
+            },
+        );
+    };
+    insert(
+        "HodgkinHuxley",
+        |p| Box::new(hodgkin_huxley::Model::new(p)),
+        hodgkin_huxley::Model::parameters(),
+    );
+    insert(
+        "IAF",
+        |p| Box::new(iaf::Model::new(p)),
+        iaf::Model::parameters(),
+    );
+    insert(
+        "Izhikevich",
+        |p| Box::new(izhikevich::Model::new(p)),
+        izhikevich::Model::parameters(),
+    );
+    insert(
+        "StaticPoisson",
+        |p| Box::new(static_poisson::Model::new(p)),
+        static_poisson::Model::parameters(),
+    );
+    insert(
+        "ConductionBasedAdaptiveThresholdLIF",
+        |p| Box::new(cb_ath_lif::Model::new(p)),
+        cb_ath_lif::Model::parameters(),
+    );
+    insert(
+        "AdEx",
+        |p| Box::new(adex::Model::new(p, adex::Synapse::Conductance)),
+        adex::Model::parameters(),
+    );
+    insert(
+        "AdExCurrentBased",
+        |p| Box::new(adex::Model::new(p, adex::Synapse::Current)),
+        adex::Model::parameters(),
+    );
+    insert(
+        "iaf_psc_delta",
+        |p| Box::new(lif::Model::new(p, lif::Synapse::Delta)),
+        lif::Model::parameters(lif::Synapse::Delta),
+    );
+    insert(
+        "iaf_psc_exp",
+        |p| Box::new(lif::Model::new(p, lif::Synapse::CurrentExp)),
+        lif::Model::parameters(lif::Synapse::CurrentExp),
+    );
+    insert(
+        "iaf_psc_alpha",
+        |p| Box::new(lif::Model::new(p, lif::Synapse::CurrentAlpha)),
+        lif::Model::parameters(lif::Synapse::CurrentAlpha),
+    );
+    insert(
+        "iaf_cond_exp",
+        |p| Box::new(lif::Model::new(p, lif::Synapse::ConductanceExp)),
+        lif::Model::parameters(lif::Synapse::ConductanceExp),
+    );
+    insert(
+        "iaf_cond_alpha",
+        |p| Box::new(lif::Model::new(p, lif::Synapse::ConductanceAlpha)),
+        lif::Model::parameters(lif::Synapse::ConductanceAlpha),
+    );
+    insert(
+        "inhomogeneous_poisson_generator",
+        |p| Box::new(inhomogeneous_poisson::Model::new(p)),
+        inhomogeneous_poisson::Model::parameters(),
+    );
+    insert(
+        "spike_generator",
+        |p| Box::new(spike_generator::Model::new(p)),
+        spike_generator::Model::parameters(),
+    );
+    models
+}
+
+// `parameters` lists every key the factory accepts with its default value;
+// keys outside of it are rejected before the factory is called.
+pub fn register(name: &str, factory: NeuronFactory, parameters: Parameters) -> Result<(), String> {
+    let mut models = MODELS.write().unwrap();
+    if models.contains_key(name) {
+        return Err(format!("neuron model {} is already registered", name));
+    }
+    models.insert(
+        name.to_string(),
+        Entry {
+            factory,
+            parameters,
+        },
+    );
+    Ok(())
+}
+
+pub fn is_registered(name: &str) -> bool {
+    MODELS.read().unwrap().contains_key(name)
+}
+
+pub fn names() -> Vec<String> {
+    let mut names: Vec<String> = MODELS.read().unwrap().keys().cloned().collect();
+    names.sort();
+    names
+}
+
+pub fn parameters(name: &str) -> Result<Parameters, String> {
+    match MODELS.read().unwrap().get(name) {
+        Some(entry) => Ok(entry.parameters.clone()),
+        None => Err(format!("unknown neuron model {}", name)),
+    }
+}
+
+pub fn build(name: &str, params: &Parameters) -> Result<Box<dyn Neuron>, String> {
+    let entry = match MODELS.read().unwrap().get(name) {
+        Some(entry) => entry.clone(),
+        None => return Err(format!("unknown neuron model {}", name)),
+    };
+    check_params(params, &entry.parameters)?;
+    Ok((entry.factory)(params))
+}
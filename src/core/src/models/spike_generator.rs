@@ -0,0 +1,259 @@
+// Copyright (c) 2019 Libgirl
+//
+// Released under Apache 2.0 license as described in the file LICENSE.txt.
+
+// Spike generator emitting a given spike train, optionally repeated every
+// `period` ms. Spikes falling in the same step are sent as one event with a
+// multiplicity.
+use crate::events::SpikeEvent;
+use crate::models::{check_params, unknown_property, Neuron, NeuronActivity};
+use crate::network::Network;
+use crate::{Double, Index, Parameters, Time};
+use std::fs;
+
+pub struct Model {
+    period: Double, // 0 when the train is not repeated
+    spike_times: Vec<Time>,
+    next: usize,  // index of the next spike in the train
+    origin: Time, // start of the current repetition
+    precise_timing: bool,
+    nid: Index,
+    is_record_spikes: bool,
+    spike_records: Vec<Vec<Time>>,
+}
+
+impl Model {
+    pub fn parameters() -> Parameters {
+        let mut params = Parameters::new();
+        params.insert("period".to_string(), 0.);
+        params
+    }
+
+    pub fn new(params: &Parameters) -> Model {
+        Model {
+            period: params.get("period").cloned().unwrap_or(0.),
+            spike_times: Vec::new(),
+            next: 0,
+            origin: 0.,
+            precise_timing: false,
+            nid: -1,
+            is_record_spikes: false,
+            spike_records: Vec::new(),
+        }
+    }
+}
+
+impl Default for Model {
+    fn default() -> Model {
+        Model::new(&Model::parameters())
+    }
+}
+
+// Reads spike trains from a text file with one `<index> <time>` pair per
+// line, where index is the position of the neuron in a population of
+// `size` neurons. Empty lines and lines starting with `#` are skipped.
+pub fn read_spike_times(path: &str, size: usize) -> Result<Vec<Vec<Time>>, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    let mut trains = vec![Vec::new(); size];
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let error = || format!("{}:{}: expected an index and a time", path, number + 1);
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() != 2 {
+            return Err(error());
+        }
+        let index: usize = fields[0].parse().map_err(|_| error())?;
+        let time: Time = fields[1].parse().map_err(|_| error())?;
+        if !time.is_finite() || time < 0. {
+            return Err(format!("{}:{}: invalid time {}", path, number + 1, time));
+        }
+        if index >= size {
+            return Err(format!(
+                "{}:{}: index {} is out of range for a population of {} neurons",
+                path,
+                number + 1,
+                index,
+                size
+            ));
+        }
+        trains[index].push(time);
+    }
+    for train in trains.iter_mut() {
+        train.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    }
+    Ok(trains)
+}
+
+impl Neuron for Model {
+    fn name(&self) -> String {
+        String::from("spike_generator")
+    }
+
+    fn set_params(&mut self, params: &Parameters) -> Result<(), String> {
+        check_params(params, &Model::parameters())?;
+        if let Some(&period) = params.get("period") {
+            self.set_property("period".to_string(), period)?;
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, t: Double) -> NeuronActivity {
+        let end = t + Network::resolution();
+        let mut count = 0;
+        let mut first = None;
+        loop {
+            if self.next == self.spike_times.len() {
+                if self.period > 0. && !self.spike_times.is_empty() {
+                    self.next = 0;
+                    self.origin += self.period;
+                    continue;
+                }
+                break;
+            }
+            let time = self.origin + self.spike_times[self.next];
+            if time >= end {
+                break;
+            }
+            // spikes set to times that have already passed are skipped
+            if time >= t {
+                count += 1;
+                if first.is_none() {
+                    first = Some(time - t);
+                }
+            }
+            self.next += 1;
+        }
+
+        match first {
+            Some(offset) => {
+                let mut se = SpikeEvent::new();
+                se.set_multiplicity(count as Double);
+                let offset = if self.precise_timing { offset } else { 0. };
+                se.set_offset(offset);
+                if self.is_record_spikes {
+                    let spike_record_index = self.spike_records.len() - 1;
+                    self.spike_records[spike_record_index].push(t + offset);
+                }
+                NeuronActivity::Fires(se)
+            }
+            None => NeuronActivity::Silent,
+        }
+    }
+
+    fn handle_spike(&mut self, _event: SpikeEvent) {}
+
+    fn set_neuron_id(&mut self, nid: i64) {
+        self.nid = nid;
+    }
+
+    fn neuron_id(&self) -> i64 {
+        self.nid
+    }
+
+    fn set_precise_timing(&mut self, enabled: bool) -> Result<(), String> {
+        self.precise_timing = enabled;
+        Ok(())
+    }
+
+    fn check_spike_times(&self, times: &[Time]) -> Result<(), String> {
+        if times.iter().any(|t| !t.is_finite() || *t < 0.) {
+            return Err("spike times must be finite and non-negative".to_string());
+        }
+        let last = times.iter().cloned().fold(0., Time::max);
+        if self.period > 0. && last >= self.period {
+            return Err(format!(
+                "spike times must be shorter than the period {}",
+                self.period
+            ));
+        }
+        Ok(())
+    }
+
+    fn set_spike_times(&mut self, times: &[Time]) -> Result<(), String> {
+        self.check_spike_times(times)?;
+        let mut times = times.to_vec();
+        times.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        self.spike_times = times;
+        self.next = 0;
+        self.origin = 0.;
+        Ok(())
+    }
+
+    // Spikes are replayed from the beginning of the train, those in the past
+    // of the clock being skipped.
+    fn reset_state(&mut self, _reset_thresholds: bool) {
+        self.next = 0;
+        self.origin = 0.;
+    }
+
+    // Steps before the one of the next spike, one less against rounding.
+    fn quiet_steps(&self, t: Time) -> Option<usize> {
+        let next = self.spike_times[self.next..]
+            .iter()
+            .map(|s| self.origin + s)
+            .find(|&time| time >= t);
+        let next = match next {
+            Some(time) => Some(time),
+            None if self.period > 0. && !self.spike_times.is_empty() => {
+                // the next spike is in the repetition containing t or the
+                // following one
+                let repetition = ((t - self.origin) / self.period).floor().max(1.);
+                (0..2)
+                    .flat_map(|k| {
+                        let origin = self.origin + (repetition + k as Double) * self.period;
+                        self.spike_times.iter().map(move |s| origin + s)
+                    })
+                    .find(|&time| time >= t)
+            }
+            None => None,
+        };
+        next.map(|time| {
+            let steps = ((time - t) / Network::resolution()).floor() as usize;
+            steps.saturating_sub(1)
+        })
+    }
+
+    fn new_spike_record(&mut self) {
+        self.spike_records.push(Vec::new());
+    }
+
+    fn set_spike_recording(&mut self, is_on: bool) {
+        self.is_record_spikes = is_on;
+    }
+
+    fn clear_spike_records(&mut self) {
+        self.spike_records.clear();
+    }
+
+    fn get_spike_records(&self) -> Vec<Vec<Time>> {
+        self.spike_records.clone()
+    }
+
+    fn property_names(&self) -> Vec<String> {
+        ["period"].iter().map(|s| s.to_string()).collect()
+    }
+
+    fn get_property(&self, name: String) -> Result<Double, String> {
+        match name.as_ref() {
+            "period" => Ok(self.period),
+            _ => Err(unknown_property(&name)),
+        }
+    }
+
+    fn set_property(&mut self, name: String, value: Double) -> Result<(), String> {
+        match name.as_ref() {
+            "period" => {
+                let last = self.spike_times.last().cloned().unwrap_or(0.);
+                if value < 0. || (value > 0. && last >= value) {
+                    return Err(format!("invalid period {}", value));
+                }
+                self.period = value;
+            }
+            _ => return Err(unknown_property(&name)),
+        }
+        Ok(())
+    }
+}
@@ -4,12 +4,15 @@
 
 // Static Poisson Spike Neuron
 use crate::events::SpikeEvent;
-use crate::models::{Neuron, NeuronActivity};
+use crate::groups::NeuronGroup;
+use crate::models::{check_params, unknown_property, Neuron, NeuronActivity};
 use crate::network::Network;
 use crate::{Double, Index, Parameters, Time};
+use rand::Rng;
 
 pub struct Model {
     freq: f64, //in Hertz
+    precise_timing: bool,
     nid: Index,
 }
 
@@ -33,6 +36,7 @@ impl Model {
 
         Model {
             freq: freq,
+            precise_timing: false,
             nid: -1,
         }
     }
@@ -50,9 +54,12 @@ impl Neuron for Model {
         String::from("StaticPoisson")
     }
 
-    fn set_params(&mut self, params: &Parameters) {
-        let freq = Model::get_or_default(params, "freq");
-        self.freq = freq;
+    fn set_params(&mut self, params: &Parameters) -> Result<(), String> {
+        check_params(params, &Model::parameters())?;
+        if let Some(&freq) = params.get("freq") {
+            self.freq = freq;
+        }
+        Ok(())
     }
 
     fn update(&mut self, _t: Double) -> NeuronActivity {
@@ -62,7 +69,11 @@ impl Neuron for Model {
         let rng_value = rand::random::<f64>();
 
         if step_freq >= 1. || step_freq > rng_value {
-            let se = SpikeEvent::new();
+            let mut se = SpikeEvent::new();
+            if self.precise_timing {
+                // the spike times of a Poisson process are uniform within the step
+                se.set_offset(rand::random::<f64>() * resolution);
+            }
             activity = NeuronActivity::Fires(se);
         }
 
@@ -71,6 +82,15 @@ impl Neuron for Model {
 
     fn handle_spike(&mut self, _event: SpikeEvent) {}
 
+    // A silent generator sleeps until its rate is changed.
+    fn quiet_steps(&self, _t: Time) -> Option<usize> {
+        if self.freq > 0. {
+            Some(0)
+        } else {
+            None
+        }
+    }
+
     fn set_neuron_id(&mut self, nid: i64) {
         self.nid = nid;
     }
@@ -79,6 +99,11 @@ impl Neuron for Model {
         self.nid
     }
 
+    fn set_precise_timing(&mut self, enabled: bool) -> Result<(), String> {
+        self.precise_timing = enabled;
+        Ok(())
+    }
+
     fn new_spike_record(&mut self) {}
 
     fn set_spike_recording(&mut self, _is_on: bool) {}
@@ -89,12 +114,80 @@ impl Neuron for Model {
         Vec::new()
     }
 
-    fn get_property(&self, name: String) -> Double {
+    fn property_names(&self) -> Vec<String> {
+        ["freq"].iter().map(|s| s.to_string()).collect()
+    }
+
+    fn get_property(&self, name: String) -> Result<Double, String> {
+        match name.as_ref() {
+            "freq" => Ok(self.freq),
+            _ => Err(unknown_property(&name)),
+        }
+    }
+
+    fn set_property(&mut self, name: String, value: Double) -> Result<(), String> {
         match name.as_ref() {
-            "freq" => self.freq,
-            _ => 0.,
+            "freq" => self.freq = value,
+            _ => return Err(unknown_property(&name)),
         }
+        Ok(())
+    }
+}
+
+// Static Poisson generators with their rates in an array.
+pub struct Group {
+    freq: Vec<f64>,
+}
+
+impl Group {
+    pub fn new(size: usize, params: &Parameters) -> Result<Group, String> {
+        check_params(params, &Model::parameters())?;
+        Ok(Group {
+            freq: vec![Model::get_or_default(params, "freq"); size],
+        })
     }
+}
 
-    fn set_property(&mut self, _name: String, _value: Double) {}
+impl NeuronGroup for Group {
+    fn name(&self) -> String {
+        String::from("StaticPoisson")
+    }
+
+    fn len(&self) -> usize {
+        self.freq.len()
+    }
+
+    fn update_group(&mut self, _t: Time, fired: &mut Vec<usize>) {
+        let resolution = Network::resolution();
+        let mut rng = rand::thread_rng();
+        for (i, freq) in self.freq.iter().enumerate() {
+            let step_freq = freq * resolution / 1000.;
+            if step_freq >= 1. || step_freq > rng.gen::<f64>() {
+                fired.push(i);
+            }
+        }
+    }
+
+    fn handle_spike(&mut self, _i: usize, _event: SpikeEvent) {}
+
+    fn reset_state(&mut self) {}
+
+    fn property_names(&self) -> Vec<String> {
+        ["freq"].iter().map(|s| s.to_string()).collect()
+    }
+
+    fn get_property(&self, i: usize, name: String) -> Result<Double, String> {
+        match name.as_ref() {
+            "freq" => Ok(self.freq[i]),
+            _ => Err(unknown_property(&name)),
+        }
+    }
+
+    fn set_property(&mut self, i: usize, name: String, value: Double) -> Result<(), String> {
+        match name.as_ref() {
+            "freq" => self.freq[i] = value,
+            _ => return Err(unknown_property(&name)),
+        }
+        Ok(())
+    }
 }
@@ -2,21 +2,30 @@
 //
 // Released under Apache 2.0 license as described in the file LICENSE.txt.
 
+use crate::connection_spec::ConnectionSpec;
 use crate::connection_supervisor::ConnectionSupervisor;
-use crate::connections::{Connection, ConnectionInfo};
-use crate::connectors::Connector;
+use crate::connections::{self, Connection, ConnectionInfo, PostSynapticEffect};
+use crate::connectors::{self, Connector};
+use crate::devices::{CurrentGenerator, CurrentSource};
+use crate::encoders::Encoder;
 use crate::events::{Event, SpikeEvent};
-use crate::models::cb_ath_lif;
-use crate::models::hodgkin_huxley;
-use crate::models::iaf;
-use crate::models::izhikevich;
-use crate::models::static_poisson;
+use crate::groups::{self, GroupMember, NeuronGroup};
+use crate::homeostasis::{ScalingState, SynapticScaling, ThresholdAdaptation};
+use crate::hooks::{ConditionWatch, HookState, HookTrigger, RunControl, StepHook, StopCondition};
+use crate::models::inhomogeneous_poisson::RateSchedule;
+use crate::models::registry;
+use crate::models::spike_generator;
+use crate::models::Integration;
 use crate::models::Neuron;
 use crate::models::NeuronActivity;
 use crate::models::NeuronType;
+use crate::parameters::{self, ParameterSpec};
 use crate::populations::Population;
 use crate::{Double, Index, Num, Parameters, Time};
 use lazy_static::lazy_static;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, BinaryHeap, HashSet};
+use std::mem;
 use std::sync::Mutex;
 
 pub struct NetworkConfig {
@@ -27,6 +36,94 @@ lazy_static! {
     static ref NETWORK_CONFIG: Mutex<NetworkConfig> = Mutex::new(NetworkConfig { resolution: 0.5 });
 }
 
+// Normalization of the incoming weights of a projection applied during
+// `Network::run`, every `interval` ms of simulated time or at the end of each
+// run when there is no interval.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WeightNormalization {
+    pub connections: Vec<Num>,
+    pub target_sum: Double,
+    #[serde(default)]
+    pub interval: Option<Time>,
+}
+
+// What `Network::reset_state_with` resets besides the dynamic variables of
+// neurons and the traces of plastic connections.
+#[derive(Clone, Debug, Default)]
+pub struct ResetOptions {
+    pub reset_thresholds: bool,
+    pub rewind_clock: bool,
+}
+
+custom_derive! {
+    #[derive(Copy, Clone, Debug, PartialEq, EnumDisplay, EnumFromStr)]
+    pub enum Engine {
+        // every neuron is updated at every step
+        TimeDriven,
+        // neurons are only updated when they receive input or may fire, and
+        // are otherwise advanced analytically over the steps they sleep
+        EventDriven,
+    }
+}
+
+// Bookkeeping of the event-driven engine within a run, steps being counted
+// from the start of the run.
+struct Schedule {
+    advanced: Vec<usize>,     // steps each neuron has been advanced over
+    wake: Vec<Option<usize>>, // step at which each sleeping neuron is due
+    queue: BinaryHeap<Reverse<(usize, Num)>>,
+    next: BTreeSet<Num>, // neurons receiving input for the next step
+    driven: Vec<Num>,    // targets of current generators, never asleep
+}
+
+impl Schedule {
+    fn new(neurons: usize, driven: Vec<Num>) -> Schedule {
+        Schedule {
+            advanced: vec![0; neurons],
+            wake: vec![Some(0); neurons],
+            queue: BinaryHeap::new(),
+            next: (0..neurons).collect(),
+            driven,
+        }
+    }
+
+    // Neurons to update at a step, in the order of the time-driven engine.
+    fn due(&mut self, step: usize) -> BTreeSet<Num> {
+        let mut due = mem::take(&mut self.next);
+        while let Some(&Reverse((wake, n))) = self.queue.peek() {
+            if wake > step {
+                break;
+            }
+            self.queue.pop();
+            if self.wake[n] == Some(wake) {
+                due.insert(n);
+            }
+        }
+        due.extend(self.driven.iter().cloned());
+        due
+    }
+
+    // Makes every neuron due at a step, once all have been advanced to it.
+    fn restart(&mut self, step: usize) {
+        for (advanced, wake) in self.advanced.iter_mut().zip(self.wake.iter_mut()) {
+            *advanced = step;
+            *wake = None;
+        }
+        self.queue.clear();
+        self.next = (0..self.advanced.len()).collect();
+    }
+
+    fn sleep(&mut self, n: Num, wake: Option<usize>) {
+        self.wake[n] = wake;
+        if let Some(step) = wake {
+            self.queue.push(Reverse((step, n)));
+        }
+    }
+}
+
+// A callback of `run_with_callback` with its interval.
+type IntervalCallback<'a> = (Time, &'a mut dyn FnMut(&mut Network, Time) -> RunControl);
+
 pub struct Network {
     neurons: Vec<Box<Neuron>>,
     populations: Vec<Box<Population>>,
@@ -35,6 +132,17 @@ pub struct Network {
     next_population_id: usize,
     recording_neuron_ids: Vec<Num>,
     start_time: Double,
+    precise_timing: bool,
+    current_generators: Vec<CurrentGenerator>,
+    weight_normalizations: Vec<(WeightNormalization, Time)>, // with the next due time
+    synaptic_scalings: Vec<ScalingState>,
+    engine: Engine,
+    groups: Vec<(Num, Box<dyn NeuronGroup>)>, // with the id of the first member
+    group_of: Vec<Option<usize>>,             // group of each neuron
+    step_hooks: Vec<HookState>,
+    next_hook_id: Num,
+    watched_spikes: Option<(HashSet<Num>, usize)>, // neurons counted by run_until
+    running: bool,                                 // between begin_run and end_run
 }
 
 impl Network {
@@ -47,29 +155,50 @@ impl Network {
             next_population_id: 0,
             recording_neuron_ids: Vec::new(),
             start_time: 0.,
+            precise_timing: false,
+            current_generators: Vec::new(),
+            weight_normalizations: Vec::new(),
+            synaptic_scalings: Vec::new(),
+            engine: Engine::TimeDriven,
+            groups: Vec::new(),
+            group_of: Vec::new(),
+            step_hooks: Vec::new(),
+            next_hook_id: 0,
+            watched_spikes: None,
+            running: false,
         }
     }
 
-    pub fn clear(&mut self) {
+    // Neurons cannot be added or removed while a run is in progress, as the
+    // event-driven schedule is sized when the run starts.
+    fn check_not_running(&self, action: &str) -> Result<(), String> {
+        if self.running {
+            Err(format!("cannot {} during a run", action))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn clear(&mut self) -> Result<(), String> {
+        self.check_not_running("clear the network")?;
         self.neurons.clear();
         self.populations.clear();
         self.connection_supervisor.clear();
         self.recording_neuron_ids.clear();
+        self.current_generators.clear();
+        self.weight_normalizations.clear();
+        self.synaptic_scalings.clear();
+        self.groups.clear();
+        self.group_of.clear();
+        self.step_hooks.clear();
         self.next_neuron_id = 0;
         self.next_population_id = 0;
-        self.start_time = 0.
+        self.start_time = 0.;
+        Ok(())
     }
 
-    pub fn build_neuron(ntype: NeuronType, params: &Parameters) -> Box<Neuron> {
-        match ntype {
-            NeuronType::HodgkinHuxley => Box::new(hodgkin_huxley::Model::default()),
-            NeuronType::IAF => Box::new(iaf::Model::default()),
-            NeuronType::Izhikevich => Box::new(izhikevich::Model::new(params)),
-            NeuronType::StaticPoisson => Box::new(static_poisson::Model::new(params)),
-            NeuronType::ConductionBasedAdaptiveThresholdLIF => {
-                Box::new(cb_ath_lif::Model::new(params))
-            }
-        }
+    pub fn build_neuron(ntype: NeuronType, params: &Parameters) -> Result<Box<Neuron>, String> {
+        registry::build(&ntype.to_string(), params)
     }
 
     pub fn create(
@@ -77,34 +206,136 @@ impl Network {
         size: usize,
         ntype: NeuronType,
         params: &Parameters,
-    ) -> Result<Population, &'static str> {
+    ) -> Result<Population, String> {
+        self.create_model(size, &ntype.to_string(), params)
+    }
+
+    // Creates a population of a model registered in `models::registry`.
+    pub fn create_model(
+        &mut self,
+        size: usize,
+        model: &str,
+        params: &Parameters,
+    ) -> Result<Population, String> {
         if size == 0 {
-            Err("invalid size")
+            Err("invalid size".to_string())
         } else {
-            let mut ids: Vec<Index> = Vec::new();
-            for _ in 0..size {
-                let neuron = Network::build_neuron(ntype, &params);
-                let id = self.add_neuron(neuron);
+            let per_neuron = vec![params.clone(); size];
+            self.create_from(model, &per_neuron)
+        }
+    }
 
-                self.neurons[id].set_neuron_id(id as i64);
-                ids.push(id as i64);
+    // Creates a population whose parameters may differ from neuron to neuron,
+    // given as arrays or random distributions.
+    pub fn create_heterogeneous(
+        &mut self,
+        size: usize,
+        model: &str,
+        spec: &ParameterSpec,
+    ) -> Result<Population, String> {
+        if size == 0 {
+            return Err("invalid size".to_string());
+        }
+        let per_neuron = parameters::resolve(spec, size)?;
+        self.create_from(model, &per_neuron)
+    }
+
+    fn create_from(
+        &mut self,
+        model: &str,
+        per_neuron: &[Parameters],
+    ) -> Result<Population, String> {
+        self.check_not_running("create neurons")?;
+        let mut neurons = Vec::new();
+        for params in per_neuron {
+            let mut neuron = registry::build(model, params)?;
+            if self.precise_timing {
+                neuron.set_precise_timing(true)?;
             }
-            let population_id = self.next_population_id;
-            self.next_population_id = self.next_population_id + 1;
+            neurons.push(neuron);
+        }
+
+        let mut ids: Vec<Index> = Vec::new();
+        for neuron in neurons {
+            let id = self.add_neuron(neuron);
+
+            self.neurons[id].set_neuron_id(id as i64);
+            ids.push(id as i64);
+        }
+        let population_id = self.next_population_id;
+        self.next_population_id += 1;
+
+        let population = Population::new(population_id, &ids);
+        let population_box = Box::new(population.clone());
+        self.populations.push(population_box);
+        Ok(population)
+    }
+
+    // Creates a population kept as a neuron group, whose neurons share the
+    // model and are updated together. Only some models have a group
+    // implementation, see `groups::build`.
+    pub fn create_group(
+        &mut self,
+        size: usize,
+        model: &str,
+        params: &Parameters,
+    ) -> Result<Population, String> {
+        if size == 0 {
+            return Err("invalid size".to_string());
+        }
+        self.check_not_running("create neurons")?;
+        if self.precise_timing {
+            return Err(format!(
+                "{} groups cannot time their spikes within a step",
+                model
+            ));
+        }
+        let group = groups::build(model, size, params)?;
+        let first = self.next_neuron_id;
+        let mut ids: Vec<Index> = Vec::new();
+        for _ in 0..size {
+            let id = self.add_neuron(Box::new(GroupMember::new(group.name())));
+            self.neurons[id].set_neuron_id(id as i64);
+            self.group_of[id] = Some(self.groups.len());
+            ids.push(id as i64);
+        }
+        self.groups.push((first, group));
+        let population_id = self.next_population_id;
+        self.next_population_id += 1;
+
+        let population = Population::new(population_id, &ids);
+        self.populations.push(Box::new(population.clone()));
+        Ok(population)
+    }
+
+    // Group of a neuron and position of the neuron in it.
+    fn group_member(&self, id: Num) -> Option<(usize, usize)> {
+        self.group_of[id].map(|g| (g, id - self.groups[g].0))
+    }
 
-            let population = Population::new(population_id, &ids);
-            let population_box = Box::new(population.clone());
-            self.populations.push(population_box);
-            Ok(population)
+    pub fn set_neuron_params(&mut self, id: Num, params: &Parameters) -> Result<(), String> {
+        match self.neurons.get_mut(id) {
+            Some(neuron) => neuron.set_params(params),
+            None => Err(format!("unknown neuron {}", id)),
         }
     }
 
-    pub fn set_neuron_params(&mut self, id: Num, params: &Parameters) {
-        self.neurons[id].set_params(params);
+    pub fn get_population_by_id(&self, id: usize) -> Result<Box<Population>, String> {
+        self.populations
+            .get(id)
+            .cloned()
+            .ok_or_else(|| format!("unknown population {}", id))
     }
 
-    pub fn get_population_by_id(&self, id: usize) -> Box<Population> {
-        self.populations[id].clone()
+    // Registers a view or an assembly so that it can be referred to by id.
+    pub fn add_population<P: AsRef<Population>>(&mut self, pop: &P) -> Population {
+        let population_id = self.next_population_id;
+        self.next_population_id += 1;
+
+        let mut population = pop.as_ref().clone();
+        population.set_id(population_id);
+        self.populations.push(Box::new(population.clone()));
+        population
     }
 
     pub fn add_neuron(&mut self, neuron: Box<Neuron>) -> Num {
@@ -112,60 +343,577 @@ impl Network {
         self.next_neuron_id = neuron_id + 1;
 
         self.neurons.push(neuron);
+        self.group_of.push(None);
         neuron_id
     }
 
-    pub fn connect<U: Connector, T: Connection>(
-        &mut self,
-        pre: &Population,
-        post: &Population,
-        conn: &U,
-        syn: &T,
-    ) -> Vec<Num> {
-        conn.connect(pre, post, syn, &mut self.connection_supervisor)
+    pub fn connect<P, Q, U, T>(&mut self, pre: &P, post: &Q, conn: &U, syn: &T) -> Vec<Num>
+    where
+        P: AsRef<Population>,
+        Q: AsRef<Population>,
+        U: Connector,
+        T: Connection,
+    {
+        conn.connect(
+            pre.as_ref(),
+            post.as_ref(),
+            syn,
+            &mut self.connection_supervisor,
+        )
     }
 
-    pub fn connect_with_initial_weights<U: Connector, T: Connection>(
+    pub fn connect_with_initial_weights<P, Q, U, T>(
         &mut self,
-        pre: &Population,
-        post: &Population,
+        pre: &P,
+        post: &Q,
         weights: Vec<Double>,
         conn: &U,
         syn: &T,
-    ) -> Vec<Num> {
-        let conn_ids = conn.connect(pre, post, syn, &mut self.connection_supervisor);
-        let mut i = 0;
-        for conn_id in &conn_ids {
+    ) -> Result<Vec<Num>, String>
+    where
+        P: AsRef<Population>,
+        Q: AsRef<Population>,
+        U: Connector,
+        T: Connection,
+    {
+        conn.check(pre.as_ref(), post.as_ref())?;
+        let first = self.connection_supervisor.len();
+        let conn_ids = conn.connect(
+            pre.as_ref(),
+            post.as_ref(),
+            syn,
+            &mut self.connection_supervisor,
+        );
+        if weights.len() != conn_ids.len() {
+            // the connections are only known once made
+            self.connection_supervisor.truncate(first);
+            return Err(format!(
+                "expected {} weights but {} were given",
+                conn_ids.len(),
+                weights.len()
+            ));
+        }
+        for (conn_id, weight) in conn_ids.iter().zip(weights) {
             self.connection_supervisor
-                .set_weight_by_conn_id(*conn_id, weights[i]);
-            i = i + 1;
+                .set_weight_by_conn_id(*conn_id, weight);
+        }
+        Ok(conn_ids)
+    }
+
+    // Connects through the connector and synapse registries.
+    pub fn connect_spec<P, Q>(
+        &mut self,
+        pre: &P,
+        post: &Q,
+        spec: &ConnectionSpec,
+    ) -> Result<Vec<Num>, String>
+    where
+        P: AsRef<Population>,
+        Q: AsRef<Population>,
+    {
+        let conn = connectors::registry::build(&spec.connector.kind, &spec.connector.args)?;
+        let effect = spec
+            .synapse
+            .post_syn_effect
+            .unwrap_or(PostSynapticEffect::Excitatory);
+        let syn = connections::registry::build(&spec.synapse.kind, &spec.synapse.params, effect)?;
+
+        conn.check(pre.as_ref(), post.as_ref())?;
+        let first = self.connection_supervisor.len();
+        let conn_ids = conn.connect(
+            pre.as_ref(),
+            post.as_ref(),
+            &*syn,
+            &mut self.connection_supervisor,
+        );
+        if let Some(ref weights) = spec.weights {
+            if weights.len() != conn_ids.len() {
+                // the connections are only known once made
+                self.connection_supervisor.truncate(first);
+                return Err(format!(
+                    "expected {} weights but {} were given",
+                    conn_ids.len(),
+                    weights.len()
+                ));
+            }
+            for (conn_id, weight) in conn_ids.iter().zip(weights) {
+                self.connection_supervisor
+                    .set_weight_by_conn_id(*conn_id, *weight);
+            }
         }
-        conn_ids
+        Ok(conn_ids)
     }
 
     fn evolve(&mut self, step: Double) {
-        for i in 0..self.neurons.len() {
-            if let NeuronActivity::Fires(_) = self.neurons[i].update(step) {
-                let sender_id = self.neurons[i].neuron_id();
-                self.connection_supervisor.propagate(sender_id, step);
-                self.deliver_spike_event(sender_id);
+        self.inject_currents(step);
+        let mut i = 0;
+        while i < self.neurons.len() {
+            if let Some(g) = self.group_of[i] {
+                // spikes within a group reach the other members at the next
+                // step, whatever their order
+                for (n, spike) in self.update_group(g, step) {
+                    let sender_id = self.notify_spike(n, step + spike.offset());
+                    self.deliver_spike_event(sender_id, spike.offset());
+                }
+                i += self.groups[g].1.len();
+                continue;
+            }
+            if let NeuronActivity::Fires(spike) = self.neurons[i].update(step) {
+                let sender_id = self.notify_spike(i, step + spike.offset());
+                self.deliver_spike_event(sender_id, spike.offset());
             }
+            i += 1;
         }
     }
 
-    pub fn run(&mut self, t: Time) {
-        let mut step = self.start_time;
-        let steps: Double = t / Network::resolution() + self.start_time;
-        let resolution = Network::resolution();
+    // Updates a group and returns the members that fire with their spikes.
+    fn update_group(&mut self, g: usize, step: Double) -> Vec<(Num, SpikeEvent)> {
+        let mut fired = Vec::new();
+        let (first, group) = &mut self.groups[g];
+        group.update_group(step, &mut fired);
+        let first = *first;
+        let mut spikes = Vec::new();
+        for i in fired {
+            if let NeuronActivity::Fires(spike) = self.neurons[first + i].update(step) {
+                spikes.push((first + i, spike));
+            }
+        }
+        spikes
+    }
+
+    fn handle_spike(&mut self, target: Num, event: SpikeEvent) {
+        match self.group_member(target) {
+            Some((g, i)) => self.groups[g].1.handle_spike(i, event),
+            None => self.neurons[target].handle_spike(event),
+        }
+    }
+
+    // Informs plastic connections and synaptic scalings of a spike and
+    // returns the id of the sender.
+    fn notify_spike(&mut self, i: Num, t: Time) -> Index {
+        let sender_id = self.neurons[i].neuron_id();
+        self.connection_supervisor.propagate(sender_id, t);
+        for state in self.synaptic_scalings.iter_mut() {
+            state.record_spike(sender_id);
+        }
+        for hook in self.step_hooks.iter_mut() {
+            hook.record_spike(i);
+        }
+        if let Some((neurons, count)) = &mut self.watched_spikes {
+            if neurons.contains(&i) {
+                *count += 1;
+            }
+        }
+        sender_id
+    }
+
+    // One step of the event-driven engine, `index` being the step within the
+    // run. Neurons are updated in the same order as by `evolve` and catch up
+    // with the steps they slept before being updated or receiving input, so
+    // both engines agree up to rounding.
+    fn evolve_events(&mut self, schedule: &mut Schedule, index: usize, step: Double) {
+        self.inject_currents(step);
+        let next_step = step + Network::resolution();
+        let mut due = schedule.due(index);
+        while let Some(&i) = due.iter().next() {
+            due.remove(&i);
+            if let Some(g) = self.group_of[i] {
+                // groups are due at every step through their first member
+                let (first, ref group) = self.groups[g];
+                if i == first && schedule.advanced[i] == index {
+                    for n in first..first + group.len() {
+                        schedule.advanced[n] = index + 1;
+                    }
+                    for (n, spike) in self.update_group(g, step) {
+                        self.spread_spike(schedule, &mut due, index, n, step, spike.offset());
+                    }
+                }
+                continue;
+            }
+            let neuron = &mut self.neurons[i];
+            neuron.skip(index - schedule.advanced[i]);
+            schedule.advanced[i] = index + 1;
+            if let NeuronActivity::Fires(spike) = neuron.update(step) {
+                self.spread_spike(schedule, &mut due, index, i, step, spike.offset());
+            }
+            let quiet = self.neurons[i].quiet_steps(next_step);
+            schedule.sleep(i, quiet.map(|q| index + 1 + q));
+        }
+    }
+
+    // Delivers a spike of the event-driven engine, waking its targets up.
+    fn spread_spike(
+        &mut self,
+        schedule: &mut Schedule,
+        due: &mut BTreeSet<Num>,
+        index: usize,
+        i: Num,
+        step: Double,
+        offset: Double,
+    ) {
+        let sender_id = self.notify_spike(i, step + offset);
+        for t in self.find_target_conn_infos(sender_id) {
+            let target = t.target as Num;
+            let mut event = SpikeEvent::new();
+            event.set_weight(t.weight);
+            event.set_offset(offset);
+            if self.group_of[target].is_some() {
+                self.handle_spike(target, event);
+                continue;
+            }
+            // targets after the sender still receive the spike in this step,
+            // the others in the next one
+            let reached = if target > i { index } else { index + 1 };
+            let receiver = &mut self.neurons[target];
+            receiver.skip(reached - schedule.advanced[target]);
+            schedule.advanced[target] = reached;
+            receiver.handle_spike(event);
+            if target > i {
+                due.insert(target);
+            } else {
+                schedule.next.insert(target);
+            }
+        }
+    }
+
+    fn inject_currents(&mut self, step: Double) {
+        if self.current_generators.is_empty() {
+            return;
+        }
+        let dt = Network::resolution();
+        let mut currents = vec![0.; self.neurons.len()];
+        for generator in self.current_generators.iter_mut() {
+            generator.inject(step, dt, &mut currents);
+        }
+        for generator in &self.current_generators {
+            for &n in generator.targets() {
+                // targets were checked to accept currents when connected
+                let _ = self.neurons[n].set_input_current(currents[n]);
+            }
+        }
+    }
+
+    // Connects a current source to every neuron of a population and returns
+    // the id of the device.
+    pub fn connect_current<P: AsRef<Population>>(
+        &mut self,
+        source: CurrentSource,
+        pop: &P,
+    ) -> Result<Num, String> {
+        let targets: Vec<Num> = pop.as_ref().iter().map(|n| n as Num).collect();
+        for &n in &targets {
+            self.neurons[n].set_input_current(0.)?;
+        }
+        let generator = CurrentGenerator::new(source, targets)?;
+        self.current_generators.push(generator);
+        Ok(self.current_generators.len() - 1)
+    }
+
+    // Runs the network for `t / resolution` ms, the duration `run` has always
+    // had, and returns the time reached. `run_for` takes the duration in ms.
+    pub fn run(&mut self, t: Time) -> Time {
+        self.run_for(t / Network::resolution())
+    }
+
+    // Runs the network for `t` ms and returns the time reached, earlier when
+    // a step hook stops the run.
+    pub fn run_for(&mut self, t: Time) -> Time {
+        self.begin_run();
+        let reached = self.advance(t);
+        self.end_run();
+        reached
+    }
+
+    // Runs as `run_for`, calling `callback` every `interval` ms of simulated
+    // time with the network and the current time.
+    pub fn run_with_callback<F>(
+        &mut self,
+        t: Time,
+        interval: Time,
+        mut callback: F,
+    ) -> Result<Time, String>
+    where
+        F: FnMut(&mut Network, Time) -> RunControl,
+    {
+        if interval <= 0. {
+            return Err(format!("invalid callback interval {}", interval));
+        }
+        self.begin_run();
+        let reached = self.advance_with(t, Some((interval, &mut callback)));
+        self.end_run();
+        Ok(reached)
+    }
+
+    // Runs for at most `max_t` ms until the condition is met at the end of a
+    // step, and returns the time it was met or None.
+    pub fn run_until(
+        &mut self,
+        max_t: Time,
+        condition: &StopCondition,
+    ) -> Result<Option<Time>, String> {
+        let mut watch = self.watch(condition)?;
+        self.begin_run();
+        self.advance_until(max_t, &mut watch);
+        self.end_run();
+        self.unwatch();
+        Ok(watch.met)
+    }
+
+    // Starts counting the spikes of a condition, checked by `advance_until`
+    // until `unwatch`.
+    pub fn watch(&mut self, condition: &StopCondition) -> Result<ConditionWatch, String> {
+        for &n in condition.watched_neurons() {
+            if n >= self.neurons.len() {
+                return Err(format!("unknown neuron {}", n));
+            }
+        }
+        let watched = condition.watched_neurons().iter().cloned().collect();
+        let watch = ConditionWatch {
+            condition: condition.clone(),
+            met: None,
+            previous: self.condition_value(condition)?,
+        };
+        self.watched_spikes = Some((watched, 0));
+        Ok(watch)
+    }
+
+    pub fn unwatch(&mut self) {
+        self.watched_spikes = None;
+    }
+
+    // Advances as `advance` until the condition is met.
+    pub fn advance_until(&mut self, t: Time, watch: &mut ConditionWatch) -> Time {
+        if watch.met.is_some() {
+            return self.start_time;
+        }
+        let mut callback = |network: &mut Network, now: Time| {
+            let spikes = network.watched_spikes.as_ref().map_or(0, |w| w.1);
+            // the property was read when the watch started
+            let current = network
+                .condition_value(&watch.condition)
+                .unwrap_or(watch.previous);
+            let is_met = watch.condition.is_met(spikes, watch.previous, current);
+            watch.previous = current;
+            if is_met {
+                watch.met = Some(now);
+                RunControl::Stop
+            } else {
+                RunControl::Continue
+            }
+        };
+        self.advance_with(t, Some((Network::resolution(), &mut callback)))
+    }
+
+    // Value of the property of a crossing condition.
+    fn condition_value(&self, condition: &StopCondition) -> Result<Double, String> {
+        match condition {
+            StopCondition::Crosses {
+                neuron, property, ..
+            } => {
+                if *neuron >= self.neurons.len() {
+                    return Err(format!("unknown neuron {}", neuron));
+                }
+                self.neuron_property(*neuron, property.clone())
+            }
+            _ => Ok(0.),
+        }
+    }
+
+    // A run can be split into `begin_run`, which starts new spike records,
+    // any number of `advance` and `end_run`, which applies the weight
+    // normalizations without interval. The FFI does so to call its hooks
+    // with the network unlocked.
+    pub fn begin_run(&mut self) {
+        self.running = true;
         for i in 0..self.recording_neuron_ids.len() {
             self.neurons[self.recording_neuron_ids[i]].new_spike_record();
         }
+    }
+
+    pub fn advance(&mut self, t: Time) -> Time {
+        self.advance_with(t, None)
+    }
 
-        while step < steps {
-            self.evolve(step);
-            step += resolution;
+    pub fn end_run(&mut self) {
+        self.running = false;
+        for (normalization, _) in &self.weight_normalizations {
+            if normalization.interval.is_none() {
+                self.connection_supervisor
+                    .normalize_incoming(&normalization.connections, normalization.target_sum);
+            }
         }
-        self.start_time = step;
+    }
+
+    fn advance_with(&mut self, t: Time, mut callback: Option<IntervalCallback>) -> Time {
+        let resolution = Network::resolution();
+        let steps = (t / resolution).round() as usize;
+        let mut schedule = match self.engine {
+            Engine::TimeDriven => None,
+            Engine::EventDriven => {
+                let driven = self
+                    .current_generators
+                    .iter()
+                    .flat_map(|g| g.targets().iter().cloned())
+                    .chain(self.groups.iter().map(|(first, _)| *first))
+                    .collect();
+                Some(Schedule::new(self.neurons.len(), driven))
+            }
+        };
+        let mut callback_due = self.start_time + callback.as_ref().map_or(0., |c| c.0);
+
+        // steps are counted so that the clock does not drift with repeated
+        // additions of the resolution
+        let mut done = steps;
+        for i in 0..steps {
+            let step = self.start_time + i as Double * resolution;
+            match schedule {
+                Some(ref mut schedule) => self.evolve_events(schedule, i, step),
+                None => self.evolve(step),
+            }
+            let t = self.start_time + (i + 1) as Double * resolution;
+            if !self.weight_normalizations.is_empty() {
+                self.normalize_due_weights(t);
+            }
+            if !self.synaptic_scalings.is_empty() {
+                self.scale_due_weights(t);
+            }
+
+            let hooks_due = self.step_hooks.iter().any(|hook| hook.is_due(t));
+            let callback_is_due = callback.is_some() && t + resolution / 2. >= callback_due;
+            if !hooks_due && !callback_is_due {
+                continue;
+            }
+            if let Some(ref mut schedule) = schedule {
+                // hooks see and change neurons that are up to date
+                self.synchronize(schedule, i + 1);
+            }
+            let mut control = self.call_hooks(t);
+            if let (true, Some((interval, callback))) = (callback_is_due, &mut callback) {
+                callback_due += *interval;
+                if callback(self, t) == RunControl::Stop {
+                    control = RunControl::Stop;
+                }
+            }
+            if control == RunControl::Stop {
+                done = i + 1;
+                break;
+            }
+        }
+        if let Some(ref mut schedule) = schedule {
+            // sleeping neurons catch up with the end of the run
+            self.synchronize(schedule, done);
+        }
+        self.start_time += done as Double * resolution;
+        self.start_time
+    }
+
+    fn synchronize(&mut self, schedule: &mut Schedule, step: usize) {
+        for (neuron, advanced) in self.neurons.iter_mut().zip(&schedule.advanced) {
+            neuron.skip(step - advanced);
+        }
+        schedule.restart(step);
+    }
+
+    fn call_hooks(&mut self, t: Time) -> RunControl {
+        let mut hooks = mem::take(&mut self.step_hooks);
+        let mut control = RunControl::Continue;
+        for hook in hooks.iter_mut() {
+            if hook.is_due(t) && hook.call(self, t) == RunControl::Stop {
+                control = RunControl::Stop;
+            }
+        }
+        // hooks added by hooks come after
+        hooks.append(&mut self.step_hooks);
+        self.step_hooks = hooks;
+        control
+    }
+
+    // Registers a hook called during runs and returns its id. Hooks cannot
+    // remove hooks.
+    pub fn add_step_hook(&mut self, trigger: HookTrigger, hook: StepHook) -> Result<Num, String> {
+        trigger.validate(self.neurons.len())?;
+        let id = self.next_hook_id;
+        self.next_hook_id += 1;
+        self.step_hooks
+            .push(HookState::new(id, trigger, hook, self.start_time));
+        Ok(id)
+    }
+
+    pub fn remove_step_hook(&mut self, id: Num) -> Result<(), String> {
+        match self.step_hooks.iter().position(|hook| hook.id == id) {
+            Some(i) => {
+                self.step_hooks.remove(i);
+                Ok(())
+            }
+            None => Err(format!("unknown step hook {}", id)),
+        }
+    }
+
+    pub fn clear_step_hooks(&mut self) {
+        self.step_hooks.clear();
+    }
+
+    fn scale_due_weights(&mut self, t: Time) {
+        for state in self.synaptic_scalings.iter_mut() {
+            if t + Network::resolution() / 2. >= state.due {
+                let factors = state.factors();
+                for &id in &state.scaling.connections {
+                    let info = self.connection_supervisor.get_conn_info_by_id(id);
+                    let weight = info.weight * factors[&info.target];
+                    self.connection_supervisor.set_weight_by_conn_id(id, weight);
+                }
+                state.due += state.scaling.interval;
+            }
+        }
+    }
+
+    fn normalize_due_weights(&mut self, t: Time) {
+        for (normalization, due) in self.weight_normalizations.iter_mut() {
+            if let Some(interval) = normalization.interval {
+                // half a step of tolerance against rounding
+                if t + Network::resolution() / 2. >= *due {
+                    self.connection_supervisor
+                        .normalize_incoming(&normalization.connections, normalization.target_sum);
+                    *due += interval;
+                }
+            }
+        }
+    }
+
+    // Brings the network back to rest between runs while keeping its
+    // structure, weights and adaptive thresholds.
+    pub fn reset_state(&mut self) {
+        self.reset_state_with(&ResetOptions::default());
+    }
+
+    pub fn reset_state_with(&mut self, options: &ResetOptions) {
+        for neuron in self.neurons.iter_mut() {
+            neuron.reset_state(options.reset_thresholds);
+        }
+        for (_, group) in self.groups.iter_mut() {
+            group.reset_state();
+        }
+        self.connection_supervisor.reset_state();
+        for generator in self.current_generators.iter_mut() {
+            generator.reset();
+        }
+        if options.rewind_clock {
+            self.start_time = 0.;
+            for (normalization, due) in self.weight_normalizations.iter_mut() {
+                *due = normalization.interval.unwrap_or(0.);
+            }
+            for state in self.synaptic_scalings.iter_mut() {
+                state.rewind();
+            }
+            for hook in self.step_hooks.iter_mut() {
+                hook.rewind();
+            }
+        }
+    }
+
+    // Time at which the next run starts.
+    pub fn current_time(&self) -> Time {
+        self.start_time
     }
 
     pub fn resolution() -> Double {
@@ -176,6 +924,40 @@ impl Network {
         NETWORK_CONFIG.lock().unwrap().resolution = r;
     }
 
+    // When enabled, neurons time their spikes within the step instead of at
+    // its beginning and apply their inputs at the offsets the spikes carry,
+    // and STDP traces decay over the exact time between spikes. It fails,
+    // changing nothing, when a neuron cannot, and also holds for neurons
+    // created afterwards.
+    pub fn set_precise_timing(&mut self, enabled: bool) -> Result<(), String> {
+        for i in 0..self.neurons.len() {
+            if let Err(e) = self.neurons[i].set_precise_timing(enabled) {
+                for neuron in self.neurons[..i].iter_mut() {
+                    neuron.set_precise_timing(self.precise_timing)?;
+                }
+                return Err(e);
+            }
+        }
+        self.precise_timing = enabled;
+        self.connection_supervisor.set_precise_timing(enabled);
+        Ok(())
+    }
+
+    // The event-driven engine pays off when activity is sparse. Models
+    // without an analytic solution, such as conductance-based ones, are
+    // updated at every step with either engine.
+    pub fn set_engine(&mut self, engine: Engine) {
+        self.engine = engine;
+    }
+
+    pub fn engine(&self) -> Engine {
+        self.engine
+    }
+
+    pub fn precise_timing(&self) -> bool {
+        self.precise_timing
+    }
+
     pub fn get_conn_info_by_id(&self, conn_id: Num) -> ConnectionInfo {
         self.connection_supervisor.get_conn_info_by_id(conn_id)
     }
@@ -190,29 +972,31 @@ impl Network {
         v
     }
 
-    fn deliver_spike_event(&mut self, sender_id: i64) {
+    fn deliver_spike_event(&mut self, sender_id: i64, offset: Double) {
         let t_conns = self.find_target_conn_infos(sender_id);
         for t in t_conns {
             let target_id = t.target as Num;
-            let receiver = &mut self.neurons[target_id];
             let mut event = SpikeEvent::new();
             event.set_weight(t.weight);
-            receiver.handle_spike(event);
+            event.set_offset(offset);
+            self.handle_spike(target_id, event);
         }
     }
 
-    pub fn record_spikes(&mut self, population_id: usize) -> Result<(), String> {
-        let population = self.get_population_by_id(population_id);
-        for i in population.iter() {
-            self.neurons[i as usize].set_spike_recording(true);
-            self.recording_neuron_ids.push(i as usize); // notice in the future there might be cases that population shares some neurons
+    pub fn record_spikes<P: AsRef<Population>>(&mut self, pop: &P) -> Result<(), String> {
+        for i in pop.as_ref().iter() {
+            let id = i as usize;
+            // populations may share neurons, so each neuron is recorded once
+            if !self.recording_neuron_ids.contains(&id) {
+                self.neurons[id].set_spike_recording(true);
+                self.recording_neuron_ids.push(id);
+            }
         }
         Ok(())
     }
 
-    pub fn clear_spike_records(&mut self, population_id: usize) -> Result<(), String> {
-        let population = self.get_population_by_id(population_id);
-        for i in population.iter() {
+    pub fn clear_spike_records<P: AsRef<Population>>(&mut self, pop: &P) -> Result<(), String> {
+        for i in pop.as_ref().iter() {
             self.neurons[i as usize].clear_spike_records();
         }
         Ok(())
@@ -229,36 +1013,354 @@ impl Network {
         spike_records
     }
 
+    // Number of recorded spikes of each neuron of a population since its
+    // records were last cleared.
+    pub fn spike_counts<P: AsRef<Population>>(&self, pop: &P) -> Vec<usize> {
+        pop.as_ref()
+            .iter()
+            .map(|n| {
+                self.neurons[n as usize]
+                    .get_spike_records()
+                    .iter()
+                    .map(|record| record.len())
+                    .sum()
+            })
+            .collect()
+    }
+
+    // Scales the weights of a projection, such as the connection ids
+    // returned by `connect`, so that they sum to `target_sum` for each
+    // target neuron.
+    pub fn normalize_incoming_weights(
+        &mut self,
+        connections: &[Num],
+        target_sum: Double,
+    ) -> Result<(), String> {
+        self.check_normalization(connections, target_sum)?;
+        self.connection_supervisor
+            .normalize_incoming(connections, target_sum);
+        Ok(())
+    }
+
+    // Normalizes all the incoming weights of each neuron of a population.
+    pub fn normalize_population_inputs<P: AsRef<Population>>(
+        &mut self,
+        pop: &P,
+        target_sum: Double,
+    ) -> Result<(), String> {
+        let connections: Vec<Num> = pop
+            .as_ref()
+            .iter()
+            .flat_map(|n| self.connection_supervisor.get_incoming_connections(n))
+            .collect();
+        self.normalize_incoming_weights(&connections, target_sum)
+    }
+
+    // Schedules a normalization during runs and returns its id.
+    pub fn add_weight_normalization(
+        &mut self,
+        normalization: WeightNormalization,
+    ) -> Result<Num, String> {
+        self.check_normalization(&normalization.connections, normalization.target_sum)?;
+        if let Some(interval) = normalization.interval {
+            if interval <= 0. {
+                return Err(format!("invalid normalization interval {}", interval));
+            }
+        }
+        let due = self.start_time + normalization.interval.unwrap_or(0.);
+        self.weight_normalizations.push((normalization, due));
+        Ok(self.weight_normalizations.len() - 1)
+    }
+
+    pub fn clear_weight_normalizations(&mut self) {
+        self.weight_normalizations.clear();
+    }
+
+    // Replaces the neurons of a population by neurons with an adaptive
+    // threshold.
+    pub fn add_threshold_adaptation<P: AsRef<Population>>(
+        &mut self,
+        pop: &P,
+        adaptation: &ThresholdAdaptation,
+    ) -> Result<(), String> {
+        adaptation.validate()?;
+        // all neurons are checked first so that none is lost on failure
+        for i in pop.as_ref().iter() {
+            let neuron = &self.neurons[i as usize];
+            if self.group_of[i as usize].is_some() {
+                return Err(format!(
+                    "{} groups have no adaptive threshold",
+                    neuron.name()
+                ));
+            }
+            adaptation.threshold_property(&**neuron)?;
+        }
+        for i in pop.as_ref().iter() {
+            let i = i as usize;
+            // a cheap placeholder while the neuron is being wrapped
+            let placeholder = Box::new(spike_generator::Model::default());
+            let neuron = mem::replace(&mut self.neurons[i], placeholder);
+            self.neurons[i] = adaptation.attach(neuron)?;
+        }
+        Ok(())
+    }
+
+    // Schedules a synaptic scaling of a projection and returns its id.
+    pub fn add_synaptic_scaling(&mut self, scaling: SynapticScaling) -> Result<Num, String> {
+        scaling.validate()?;
+        self.check_connections(&scaling.connections)?;
+        let targets = scaling
+            .connections
+            .iter()
+            .map(|&id| self.connection_supervisor.get_conn_info_by_id(id).target)
+            .collect();
+        let state = ScalingState::new(scaling, targets, self.start_time);
+        self.synaptic_scalings.push(state);
+        Ok(self.synaptic_scalings.len() - 1)
+    }
+
+    pub fn clear_synaptic_scalings(&mut self) {
+        self.synaptic_scalings.clear();
+    }
+
+    // Enables or disables learning on the plastic connections of a
+    // projection, or of the whole network when no projection is given. The
+    // network-wide setting also holds for connections created afterwards.
+    pub fn set_plasticity(
+        &mut self,
+        connections: Option<&[Num]>,
+        enabled: bool,
+    ) -> Result<(), String> {
+        match connections {
+            Some(connections) => {
+                self.check_connections(connections)?;
+                for &id in connections {
+                    self.connection_supervisor
+                        .set_plasticity_by_conn_id(id, enabled);
+                }
+            }
+            None => self.connection_supervisor.set_plasticity(enabled),
+        }
+        Ok(())
+    }
+
+    pub fn plasticity(&self, conn_id: Num) -> bool {
+        self.connection_supervisor.plasticity_by_conn_id(conn_id)
+    }
+
+    fn check_normalization(&self, connections: &[Num], target_sum: Double) -> Result<(), String> {
+        if !target_sum.is_finite() || target_sum < 0. {
+            return Err(format!("invalid target sum {}", target_sum));
+        }
+        self.check_connections(connections)
+    }
+
+    fn check_connections(&self, connections: &[Num]) -> Result<(), String> {
+        match connections
+            .iter()
+            .find(|&&id| id >= self.connection_supervisor.len())
+        {
+            Some(id) => Err(format!("unknown connection {}", id)),
+            None => Ok(()),
+        }
+    }
+
     pub fn set_weight_by_conn_id(&mut self, conn_id: Num, weight: Double) {
         self.connection_supervisor
             .set_weight_by_conn_id(conn_id, weight);
     }
 
-    pub fn set_property(&mut self, pop_id: usize, name: String, value: Double) {
-        // FIXME: Using name
-        let pop = self.get_population_by_id(pop_id);
-        for i in pop.iter() {
-            self.neurons[i as usize].set_property(name.clone(), value);
+    pub fn set_property<P: AsRef<Population>>(
+        &mut self,
+        pop: &P,
+        name: String,
+        value: Double,
+    ) -> Result<(), String> {
+        let ids: Vec<Num> = pop.as_ref().iter().map(|i| i as Num).collect();
+        let values = vec![value; ids.len()];
+        self.set_neuron_properties(&ids, &name, &values)
+    }
+
+    pub fn set_properties<P: AsRef<Population>>(
+        &mut self,
+        pop: &P,
+        name: String,
+        values: Vec<Double>,
+    ) -> Result<(), String> {
+        let pop = pop.as_ref();
+        if values.len() != pop.size() {
+            return Err(format!(
+                "expected {} values but {} were given",
+                pop.size(),
+                values.len()
+            ));
         }
+        let ids: Vec<Num> = pop.iter().map(|i| i as Num).collect();
+        self.set_neuron_properties(&ids, &name, &values)
     }
 
-    pub fn set_properties(&mut self, pop_id: usize, name: String, values: Vec<Double>) {
-        let pop = self.get_population_by_id(pop_id);
-        let mut i = 0;
-        for n in pop.iter() {
-            self.neurons[n as usize].set_property(name.clone(), values[i]);
-            i += 1;
+    // Sets the property of every neuron or of none: the name is checked on
+    // all of them first, and the neurons already set are restored when one
+    // rejects its value.
+    fn set_neuron_properties(
+        &mut self,
+        ids: &[Num],
+        name: &str,
+        values: &[Double],
+    ) -> Result<(), String> {
+        for &id in ids {
+            if !self.property_names(id)?.iter().any(|n| n == name) {
+                let model = match self.group_member(id) {
+                    Some((g, _)) => self.groups[g].1.name(),
+                    None => self.neurons[id].name(),
+                };
+                return Err(format!("{} has no property {}", model, name));
+            }
+        }
+        let mut previous = Vec::new();
+        for (&id, &value) in ids.iter().zip(values) {
+            let old = self.neuron_property(id, name.to_string())?;
+            if let Err(e) = self.set_neuron_property(id, name.to_string(), value) {
+                for (&id, old) in ids.iter().zip(previous) {
+                    let _ = self.set_neuron_property(id, name.to_string(), old);
+                }
+                return Err(e);
+            }
+            previous.push(old);
+        }
+        Ok(())
+    }
+
+    fn neuron_property(&self, id: Num, name: String) -> Result<Double, String> {
+        match self.group_member(id) {
+            Some((g, i)) => self.groups[g].1.get_property(i, name),
+            None => self.neurons[id].get_property(name),
+        }
+    }
+
+    fn set_neuron_property(&mut self, id: Num, name: String, value: Double) -> Result<(), String> {
+        match self.group_member(id) {
+            Some((g, i)) => self.groups[g].1.set_property(i, name, value),
+            None => self.neurons[id].set_property(name, value),
         }
     }
 
-    pub fn get_property(&self, pop_id: usize, name: String) -> Vec<Double> {
+    pub fn get_property<P: AsRef<Population>>(
+        &self,
+        pop: &P,
+        name: String,
+    ) -> Result<Vec<Double>, String> {
         let mut v: Vec<Double> = Vec::new();
-        let pop = self.get_population_by_id(pop_id);
-        for i in pop.iter() {
-            let value = self.neurons[i as usize].get_property(name.clone());
-            v.push(value);
+        for i in pop.as_ref().iter() {
+            v.push(self.neuron_property(i as usize, name.clone())?);
         }
-        v
+        Ok(v)
+    }
+
+    pub fn set_integration<P: AsRef<Population>>(
+        &mut self,
+        pop: &P,
+        method: Integration,
+    ) -> Result<(), String> {
+        // checked on every neuron first so that none is changed on error
+        for i in pop.as_ref().iter() {
+            self.neurons[i as usize].check_integration(method)?;
+        }
+        for i in pop.as_ref().iter() {
+            self.neurons[i as usize].set_integration(method)?;
+        }
+        Ok(())
+    }
+
+    // Sets the spike train of each spike generator of a population, in the
+    // order of the population, or of none when one of them rejects its train.
+    pub fn set_spike_times<P: AsRef<Population>>(
+        &mut self,
+        pop: &P,
+        times: &[Vec<Time>],
+    ) -> Result<(), String> {
+        let pop = pop.as_ref();
+        if times.len() != pop.size() {
+            return Err(format!(
+                "expected {} spike trains but {} were given",
+                pop.size(),
+                times.len()
+            ));
+        }
+        for (n, train) in pop.iter().zip(times) {
+            self.neurons[n as usize].check_spike_times(train)?;
+        }
+        for (n, train) in pop.iter().zip(times) {
+            self.neurons[n as usize].set_spike_times(train)?;
+        }
+        Ok(())
+    }
+
+    pub fn load_spike_times<P: AsRef<Population>>(
+        &mut self,
+        pop: &P,
+        path: &str,
+    ) -> Result<(), String> {
+        let times = spike_generator::read_spike_times(path, pop.as_ref().size())?;
+        self.set_spike_times(pop, &times)
+    }
+
+    // Sets the rate in Hertz of each Poisson generator of a population at
+    // once, e.g. the pixel intensities of an image presented to an input
+    // layer.
+    pub fn set_rates<P: AsRef<Population>>(
+        &mut self,
+        pop: &P,
+        rates: Vec<Double>,
+    ) -> Result<(), String> {
+        self.set_properties(pop, "freq".to_string(), rates)
+    }
+
+    pub fn set_rate_schedule<P: AsRef<Population>>(
+        &mut self,
+        pop: &P,
+        schedule: &RateSchedule,
+    ) -> Result<(), String> {
+        // checked on every neuron first so that none is changed on error
+        for i in pop.as_ref().iter() {
+            self.neurons[i as usize].check_rate_schedule(schedule)?;
+        }
+        for i in pop.as_ref().iter() {
+            self.neurons[i as usize].set_rate_schedule(schedule)?;
+        }
+        Ok(())
+    }
+
+    // Encodes values into the spike trains of a population of spike
+    // generators for a presentation window starting at the current time.
+    pub fn encode<P: AsRef<Population>>(
+        &mut self,
+        pop: &P,
+        encoder: &dyn Encoder,
+        values: &[Double],
+        duration: Time,
+    ) -> Result<(), String> {
+        let size = pop.as_ref().size();
+        if encoder.neurons(values.len()) != size {
+            return Err(format!(
+                "{} values need {} neurons but the population has {}",
+                values.len(),
+                encoder.neurons(values.len()),
+                size
+            ));
+        }
+        let times = encoder.encode(values, self.start_time, duration);
+        self.set_spike_times(pop, &times)
+    }
+
+    pub fn property_names(&self, neuron_id: Num) -> Result<Vec<String>, String> {
+        if neuron_id >= self.neurons.len() {
+            return Err(format!("unknown neuron {}", neuron_id));
+        }
+        Ok(match self.group_member(neuron_id) {
+            Some((g, _)) => self.groups[g].1.property_names(),
+            None => self.neurons[neuron_id].property_names(),
+        })
     }
 }
 
@@ -9,3 +9,210 @@ pub fn rk4<F: Fn(f64) -> f64>(f: F, y: f64, dt: f64) -> f64 {
     let k4 = dt * f(y + k3);
     (k1 + 2.0 * k2 + 2.0 * k3 + k4) / 6.0
 }
+
+// Propagators of a leaky integrator with time constant tau_m driven by an
+// input decaying with time constant tau_s, over one step h. `p21` is the
+// response to an exponentially decaying input of unit initial value and
+// `p31` the response to the t * exp(-t / tau_s) part of an alpha input.
+pub fn p21(tau_m: f64, tau_s: f64, h: f64) -> f64 {
+    let a = 1. / tau_m - 1. / tau_s;
+    if a.abs() < 1e-12 {
+        h * (-h / tau_m).exp()
+    } else {
+        ((-h / tau_s).exp() - (-h / tau_m).exp()) / a
+    }
+}
+
+pub fn p31(tau_m: f64, tau_s: f64, h: f64) -> f64 {
+    let a = 1. / tau_m - 1. / tau_s;
+    if a.abs() < 1e-12 {
+        0.5 * h * h * (-h / tau_m).exp()
+    } else {
+        (-h / tau_m).exp() * ((a * h).exp() * (a * h - 1.) + 1.) / (a * a)
+    }
+}
+
+// Solvers for systems of ODEs. `f(y, dydt)` writes the derivative of the
+// state vector `y` into `dydt`; inputs are held constant over the step, so
+// the systems are autonomous.
+
+fn combine(y: &[f64], h: f64, terms: &[(f64, &[f64])], out: &mut [f64]) {
+    for i in 0..y.len() {
+        out[i] = y[i] + h * terms.iter().map(|(c, k)| c * k[i]).sum::<f64>();
+    }
+}
+
+// Advances `y` by one classical Runge-Kutta step of size dt.
+pub fn rk4_system<F: Fn(&[f64], &mut [f64])>(f: F, y: &mut [f64], dt: f64) {
+    let size = y.len();
+    let mut k1 = vec![0.; size];
+    let mut k2 = vec![0.; size];
+    let mut k3 = vec![0.; size];
+    let mut k4 = vec![0.; size];
+    let mut tmp = vec![0.; size];
+
+    f(y, &mut k1);
+    combine(y, dt, &[(0.5, &k1)], &mut tmp);
+    f(&tmp, &mut k2);
+    combine(y, dt, &[(0.5, &k2)], &mut tmp);
+    f(&tmp, &mut k3);
+    combine(y, dt, &[(1., &k3)], &mut tmp);
+    f(&tmp, &mut k4);
+    for i in 0..size {
+        y[i] += dt * (k1[i] + 2. * k2[i] + 2. * k3[i] + k4[i]) / 6.;
+    }
+}
+
+pub const DEFAULT_TOLERANCE: f64 = 1e-6;
+
+// Advances `y` by dt with Dormand-Prince 5(4) substeps whose size is chosen
+// so that the local error stays below `tolerance`, both absolute and
+// relative. `step` holds the substep size to try first and is updated for
+// the next call; a non-positive value starts from dt.
+pub fn rk45_system<F: Fn(&[f64], &mut [f64])>(
+    f: F,
+    y: &mut [f64],
+    dt: f64,
+    step: &mut f64,
+    tolerance: f64,
+) {
+    rk45_system_until(f, y, dt, step, tolerance, |_| false);
+}
+
+// As `rk45_system`, but ends after the first substep whose state satisfies
+// `stop`, such as a membrane potential past the spike threshold, and returns
+// the time reached within the step.
+pub fn rk45_system_until<F, S>(
+    f: F,
+    y: &mut [f64],
+    dt: f64,
+    step: &mut f64,
+    tolerance: f64,
+    stop: S,
+) -> f64
+where
+    F: Fn(&[f64], &mut [f64]),
+    S: Fn(&[f64]) -> bool,
+{
+    let size = y.len();
+    let mut k = vec![vec![0.; size]; 7];
+    let mut tmp = vec![0.; size];
+    let mut y5 = vec![0.; size];
+    let mut h = if *step > 0. { step.min(dt) } else { dt };
+    let mut t = 0.;
+
+    while t < dt {
+        let last = h >= dt - t;
+        if last {
+            h = dt - t;
+        }
+        f(y, &mut k[0]);
+        combine(y, h, &[(1. / 5., &k[0])], &mut tmp);
+        f(&tmp, &mut k[1]);
+        combine(y, h, &[(3. / 40., &k[0]), (9. / 40., &k[1])], &mut tmp);
+        f(&tmp, &mut k[2]);
+        combine(
+            y,
+            h,
+            &[(44. / 45., &k[0]), (-56. / 15., &k[1]), (32. / 9., &k[2])],
+            &mut tmp,
+        );
+        f(&tmp, &mut k[3]);
+        combine(
+            y,
+            h,
+            &[
+                (19372. / 6561., &k[0]),
+                (-25360. / 2187., &k[1]),
+                (64448. / 6561., &k[2]),
+                (-212. / 729., &k[3]),
+            ],
+            &mut tmp,
+        );
+        f(&tmp, &mut k[4]);
+        combine(
+            y,
+            h,
+            &[
+                (9017. / 3168., &k[0]),
+                (-355. / 33., &k[1]),
+                (46732. / 5247., &k[2]),
+                (49. / 176., &k[3]),
+                (-5103. / 18656., &k[4]),
+            ],
+            &mut tmp,
+        );
+        f(&tmp, &mut k[5]);
+        combine(
+            y,
+            h,
+            &[
+                (35. / 384., &k[0]),
+                (500. / 1113., &k[2]),
+                (125. / 192., &k[3]),
+                (-2187. / 6784., &k[4]),
+                (11. / 84., &k[5]),
+            ],
+            &mut y5,
+        );
+        f(&y5, &mut k[6]);
+
+        // difference between the fifth and the embedded fourth order solution
+        let mut error: f64 = 0.;
+        for i in 0..size {
+            let e = h
+                * (71. / 57600. * k[0][i] - 71. / 16695. * k[2][i] + 71. / 1920. * k[3][i]
+                    - 17253. / 339_200. * k[4][i]
+                    + 22. / 525. * k[5][i]
+                    - 1. / 40. * k[6][i]);
+            let scale = tolerance * (1. + y[i].abs().max(y5[i].abs()));
+            error = error.max(e.abs() / scale);
+        }
+
+        let factor = if error > 0. {
+            (0.9 * error.powf(-0.2)).clamp(0.2, 5.)
+        } else {
+            5.
+        };
+        // very small substeps are accepted so that the loop always ends
+        if error <= 1. || h <= dt * 1e-9 {
+            y.copy_from_slice(&y5);
+            if last {
+                t = dt;
+            } else {
+                t += h;
+                *step = h * factor;
+            }
+            h *= factor;
+            if stop(y) {
+                break;
+            }
+        } else {
+            h *= factor.min(1.);
+        }
+    }
+    t
+}
+
+// Exponential Euler step for systems written as dy_i/dt = a_i - b_i * y_i,
+// where `f(y, a, b)` evaluates a and b at the start of the step. Each
+// component relaxes exactly towards a_i / b_i, which keeps stiff gating
+// variables stable at large steps.
+pub fn exponential_euler_system<F: Fn(&[f64], &mut [f64], &mut [f64])>(
+    f: F,
+    y: &mut [f64],
+    dt: f64,
+) {
+    let size = y.len();
+    let mut a = vec![0.; size];
+    let mut b = vec![0.; size];
+    f(y, &mut a, &mut b);
+    for i in 0..size {
+        if b[i].abs() < 1e-12 {
+            y[i] += a[i] * dt;
+        } else {
+            let y_inf = a[i] / b[i];
+            y[i] = y_inf + (y[i] - y_inf) * (-b[i] * dt).exp();
+        }
+    }
+}
@@ -0,0 +1,98 @@
+// Copyright (c) 2019 Libgirl
+//
+// Released under Apache 2.0 license as described in the file LICENSE.txt.
+
+// Per-neuron parameter values used when creating a population
+use crate::Parameters;
+use rand::distributions::{Distribution as RandDistribution, Normal, Uniform};
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "distribution", rename_all = "snake_case")]
+pub enum Distribution {
+    Uniform { low: f64, high: f64 },
+    Normal { mean: f64, std: f64 },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ParameterValue {
+    Constant(f64),
+    Array(Vec<f64>),
+    Random(Distribution),
+}
+
+pub type ParameterSpec = HashMap<String, ParameterValue>;
+
+impl ParameterValue {
+    // Draws or picks the values of `size` neurons.
+    pub fn values(&self, size: usize) -> Result<Vec<f64>, String> {
+        match self {
+            ParameterValue::Constant(v) => Ok(vec![*v; size]),
+            ParameterValue::Array(vs) => {
+                if vs.len() == size {
+                    Ok(vs.clone())
+                } else {
+                    Err(format!(
+                        "expected {} values but {} were given",
+                        size,
+                        vs.len()
+                    ))
+                }
+            }
+            ParameterValue::Random(Distribution::Uniform { low, high }) => {
+                if low >= high {
+                    return Err(format!("invalid uniform range [{}, {})", low, high));
+                }
+                let dist = Uniform::new(*low, *high);
+                let mut rng = rand::thread_rng();
+                Ok((0..size).map(|_| dist.sample(&mut rng)).collect())
+            }
+            ParameterValue::Random(Distribution::Normal { mean, std }) => {
+                if *std < 0. {
+                    return Err(format!("invalid standard deviation {}", std));
+                }
+                let dist = Normal::new(*mean, *std);
+                let mut rng = rand::thread_rng();
+                Ok((0..size).map(|_| dist.sample(&mut rng)).collect())
+            }
+        }
+    }
+}
+
+impl From<f64> for ParameterValue {
+    fn from(v: f64) -> ParameterValue {
+        ParameterValue::Constant(v)
+    }
+}
+
+impl From<Vec<f64>> for ParameterValue {
+    fn from(vs: Vec<f64>) -> ParameterValue {
+        ParameterValue::Array(vs)
+    }
+}
+
+impl From<Distribution> for ParameterValue {
+    fn from(d: Distribution) -> ParameterValue {
+        ParameterValue::Random(d)
+    }
+}
+
+pub fn from_parameters(params: &Parameters) -> ParameterSpec {
+    params
+        .iter()
+        .map(|(k, v)| (k.clone(), ParameterValue::Constant(*v)))
+        .collect()
+}
+
+// Expands a spec into the parameters of each of the `size` neurons.
+pub fn resolve(spec: &ParameterSpec, size: usize) -> Result<Vec<Parameters>, String> {
+    let mut per_neuron: Vec<Parameters> = vec![Parameters::new(); size];
+    for (key, value) in spec {
+        let values = value.values(size).map_err(|e| format!("{}: {}", key, e))?;
+        for (params, v) in per_neuron.iter_mut().zip(values) {
+            params.insert(key.clone(), v);
+        }
+    }
+    Ok(per_neuron)
+}
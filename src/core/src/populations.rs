@@ -6,6 +6,7 @@ extern crate serde;
 extern crate serde_json;
 
 use crate::Index;
+use std::ops::Range;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Population {
@@ -27,6 +28,10 @@ impl Population {
         self.id
     }
 
+    pub fn set_id(&mut self, id: usize) {
+        self.id = id;
+    }
+
     pub fn size(&self) -> usize {
         self.size
     }
@@ -39,6 +44,55 @@ impl Population {
         }
     }
 
+    pub fn neuron_ids(&self) -> &[Index] {
+        &self.neuron_ids
+    }
+
+    pub fn contains(&self, neuron_id: Index) -> bool {
+        self.neuron_ids.contains(&neuron_id)
+    }
+
+    // Views keep the id of the population they are taken from until they are
+    // registered with `Network::add_population`.
+    pub fn slice(&self, range: Range<usize>) -> Result<Population, String> {
+        if range.start > range.end || range.end > self.size {
+            return Err(format!(
+                "invalid range {}..{} for population of size {}",
+                range.start, range.end, self.size
+            ));
+        }
+        Ok(Population::new(self.id, &self.neuron_ids[range]))
+    }
+
+    pub fn select(&self, indices: &[usize]) -> Result<Population, String> {
+        let mut ids: Vec<Index> = Vec::new();
+        for &i in indices {
+            match self.get(i) {
+                Some(id) => ids.push(id),
+                None => {
+                    return Err(format!(
+                        "invalid index {} for population of size {}",
+                        i, self.size
+                    ))
+                }
+            }
+        }
+        Ok(Population::new(self.id, &ids))
+    }
+
+    pub fn sample(&self, n: usize) -> Result<Population, String> {
+        if n > self.size {
+            return Err(format!(
+                "cannot sample {} neurons from population of size {}",
+                n, self.size
+            ));
+        }
+        let mut rng = rand::thread_rng();
+        let mut indices = rand::seq::index::sample(&mut rng, self.size, n).into_vec();
+        indices.sort();
+        self.select(&indices)
+    }
+
     pub fn print_status(&self) {
         println!("{}", serde_json::to_string(self).unwrap());
     }
@@ -52,6 +106,12 @@ impl Population {
     }
 }
 
+impl AsRef<Population> for Population {
+    fn as_ref(&self) -> &Population {
+        self
+    }
+}
+
 pub struct PopulationIter<'a> {
     pop: &'a Population,
     curr: usize,
@@ -66,3 +126,60 @@ impl<'a> Iterator for PopulationIter<'a> {
         r
     }
 }
+
+// Union of several populations, possibly of different neuron types.
+// Neurons shared by more than one member appear only once.
+#[derive(Clone)]
+pub struct Assembly {
+    members: Vec<Population>,
+    population: Population,
+}
+
+impl Assembly {
+    pub fn new(members: &[&Population]) -> Assembly {
+        let mut assembly = Assembly {
+            members: Vec::new(),
+            population: Population::new(0, &[]),
+        };
+        for member in members {
+            assembly.push(member);
+        }
+        assembly
+    }
+
+    pub fn push(&mut self, member: &Population) {
+        if self.members.is_empty() {
+            self.population.set_id(member.get_id());
+        }
+        let mut ids = self.population.neuron_ids.clone();
+        for id in member.iter() {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        self.members.push(member.clone());
+        self.population = Population::new(self.population.get_id(), &ids);
+    }
+
+    pub fn members(&self) -> &[Population] {
+        &self.members
+    }
+
+    pub fn size(&self) -> usize {
+        self.population.size()
+    }
+
+    pub fn iter(&self) -> PopulationIter<'_> {
+        self.population.iter()
+    }
+
+    pub fn population(&self) -> &Population {
+        &self.population
+    }
+}
+
+impl AsRef<Population> for Assembly {
+    fn as_ref(&self) -> &Population {
+        &self.population
+    }
+}
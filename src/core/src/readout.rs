@@ -0,0 +1,245 @@
+// Copyright (c) 2019 Libgirl
+//
+// Released under Apache 2.0 license as described in the file LICENSE.txt.
+
+// Classification from the spike counts of an output population, as used by
+// unsupervised networks such as Diehl & Cook (2015): neurons are labelled
+// with the class they respond to most and samples are classified by a vote
+// of the labelled neurons, or by a linear readout of the counts.
+use crate::Double;
+
+// Labels each neuron with the class of highest average response, given the
+// spike counts of the neurons for each sample and the labels of the samples.
+// Neurons that never fire are left unlabelled.
+pub fn label_neurons(
+    counts: &[Vec<usize>],
+    labels: &[usize],
+    classes: usize,
+) -> Result<Vec<Option<usize>>, String> {
+    if counts.len() != labels.len() {
+        return Err(format!(
+            "expected {} labels but {} were given",
+            counts.len(),
+            labels.len()
+        ));
+    }
+    if let Some(label) = labels.iter().find(|&&l| l >= classes) {
+        return Err(format!("label {} is out of range", label));
+    }
+    let neurons = counts.first().map_or(0, |c| c.len());
+    let mut responses = vec![vec![0.; classes]; neurons];
+    let mut samples = vec![0; classes];
+    for (sample, &label) in counts.iter().zip(labels) {
+        samples[label] += 1;
+        for (response, &count) in responses.iter_mut().zip(sample) {
+            response[label] += count as Double;
+        }
+    }
+    Ok(responses
+        .iter()
+        .map(|response| {
+            let averages = (0..classes).map(|c| {
+                if samples[c] == 0 {
+                    0.
+                } else {
+                    response[c] / samples[c] as Double
+                }
+            });
+            argmax(averages)
+        })
+        .collect())
+}
+
+// Index of the largest positive value, the first one among equals.
+fn argmax<I: Iterator<Item = Double>>(values: I) -> Option<usize> {
+    let mut best = None;
+    let mut max = 0.;
+    for (i, v) in values.enumerate() {
+        if v > max {
+            best = Some(i);
+            max = v;
+        }
+    }
+    best
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Voting {
+    // each firing neuron votes once for its class
+    Majority,
+    // classes are ranked by the average count of their neurons
+    Average,
+}
+
+// Predicts the class of a sample from the spike counts of labelled neurons,
+// or None when no labelled neuron fired.
+pub fn vote(
+    counts: &[usize],
+    neuron_labels: &[Option<usize>],
+    classes: usize,
+    voting: Voting,
+) -> Result<Option<usize>, String> {
+    if let Some(label) = neuron_labels.iter().flatten().find(|&&l| l >= classes) {
+        return Err(format!("label {} is out of range", label));
+    }
+    let mut scores = vec![0.; classes];
+    let mut neurons = vec![0; classes];
+    for (&count, label) in counts.iter().zip(neuron_labels) {
+        if let Some(c) = *label {
+            neurons[c] += 1;
+            scores[c] += match voting {
+                Voting::Majority if count > 0 => 1.,
+                Voting::Majority => 0.,
+                Voting::Average => count as Double,
+            };
+        }
+    }
+    if voting == Voting::Average {
+        for (score, &n) in scores.iter_mut().zip(&neurons) {
+            if n > 0 {
+                *score /= n as Double;
+            }
+        }
+    }
+    Ok(argmax(scores.into_iter()))
+}
+
+// Softmax regression on spike counts, trained by stochastic gradient
+// descent.
+#[derive(Clone, Debug)]
+pub struct LinearReadout {
+    pub weights: Vec<Vec<Double>>, // one row per class, the bias last
+    pub learning_rate: Double,
+    pub epochs: usize,
+}
+
+impl LinearReadout {
+    pub fn new(neurons: usize, classes: usize) -> LinearReadout {
+        LinearReadout {
+            weights: vec![vec![0.; neurons + 1]; classes],
+            learning_rate: 0.01,
+            epochs: 50,
+        }
+    }
+
+    pub fn scores(&self, counts: &[usize]) -> Vec<Double> {
+        let bias = self.weights.first().map_or(0, |w| w.len() - 1);
+        let logits: Vec<Double> = self
+            .weights
+            .iter()
+            .map(|w| {
+                w[bias]
+                    + w.iter()
+                        .zip(counts)
+                        .map(|(w, &c)| w * c as Double)
+                        .sum::<Double>()
+            })
+            .collect();
+        let max = logits
+            .iter()
+            .cloned()
+            .fold(Double::NEG_INFINITY, Double::max);
+        let exps: Vec<Double> = logits.iter().map(|l| (l - max).exp()).collect();
+        let total: Double = exps.iter().sum();
+        exps.iter().map(|e| e / total).collect()
+    }
+
+    pub fn predict(&self, counts: &[usize]) -> usize {
+        let scores = self.scores(counts);
+        (0..scores.len()).fold(0, |best, c| if scores[c] > scores[best] { c } else { best })
+    }
+
+    pub fn train(&mut self, counts: &[Vec<usize>], labels: &[usize]) -> Result<(), String> {
+        let neurons = self.weights.first().map_or(0, |w| w.len() - 1);
+        if counts.len() != labels.len() {
+            return Err(format!(
+                "expected {} labels but {} were given",
+                counts.len(),
+                labels.len()
+            ));
+        }
+        if let Some(sample) = counts.iter().find(|c| c.len() != neurons) {
+            return Err(format!(
+                "expected counts of {} neurons but {} were given",
+                neurons,
+                sample.len()
+            ));
+        }
+        if let Some(label) = labels.iter().find(|&&l| l >= self.weights.len()) {
+            return Err(format!("label {} is out of range", label));
+        }
+        for _ in 0..self.epochs {
+            for (sample, &label) in counts.iter().zip(labels) {
+                let scores = self.scores(sample);
+                for (c, w) in self.weights.iter_mut().enumerate() {
+                    let target = if c == label { 1. } else { 0. };
+                    let error = scores[c] - target;
+                    for (w, &count) in w.iter_mut().zip(sample) {
+                        *w -= self.learning_rate * error * count as Double;
+                    }
+                    w[neurons] -= self.learning_rate * error;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Counts of actual classes (rows) against predicted classes (columns).
+// Samples without a prediction count as errors.
+#[derive(Clone, Debug)]
+pub struct ConfusionMatrix {
+    pub counts: Vec<Vec<usize>>,
+    pub unclassified: Vec<usize>,
+}
+
+impl ConfusionMatrix {
+    pub fn new(classes: usize) -> ConfusionMatrix {
+        ConfusionMatrix {
+            counts: vec![vec![0; classes]; classes],
+            unclassified: vec![0; classes],
+        }
+    }
+
+    pub fn from_predictions(
+        actual: &[usize],
+        predicted: &[Option<usize>],
+        classes: usize,
+    ) -> ConfusionMatrix {
+        let mut matrix = ConfusionMatrix::new(classes);
+        for (&a, &p) in actual.iter().zip(predicted) {
+            matrix.add(a, p);
+        }
+        matrix
+    }
+
+    pub fn add(&mut self, actual: usize, predicted: Option<usize>) {
+        match predicted {
+            Some(p) => self.counts[actual][p] += 1,
+            None => self.unclassified[actual] += 1,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.counts.iter().flatten().sum::<usize>() + self.unclassified.iter().sum::<usize>()
+    }
+
+    pub fn accuracy(&self) -> Double {
+        let correct: usize = (0..self.counts.len()).map(|c| self.counts[c][c]).sum();
+        if self.total() == 0 {
+            0.
+        } else {
+            correct as Double / self.total() as Double
+        }
+    }
+
+    // Fraction of the samples of a class that are predicted correctly.
+    pub fn recall(&self, class: usize) -> Double {
+        let samples = self.counts[class].iter().sum::<usize>() + self.unclassified[class];
+        if samples == 0 {
+            0.
+        } else {
+            self.counts[class][class] as Double / samples as Double
+        }
+    }
+}
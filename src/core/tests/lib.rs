@@ -28,3 +28,2024 @@ fn test_setting_network_resolution() {
     let actual = Network::resolution();
     assert_eq!(actual, expect);
 }
+
+#[test]
+fn population_views() {
+    use wheatnnleek::populations::Population;
+    let pop = Population::new(3, &[10, 11, 12, 13, 14]);
+    let first = pop.slice(0..2).unwrap();
+    assert_eq!(first.neuron_ids(), &[10, 11]);
+    assert_eq!(first.get_id(), 3);
+    let picked = pop.select(&[4, 1]).unwrap();
+    assert_eq!(picked.neuron_ids(), &[14, 11]);
+    assert!(pop.slice(3..6).is_err());
+    assert!(pop.select(&[5]).is_err());
+    let sample = pop.sample(3).unwrap();
+    assert_eq!(sample.size(), 3);
+    assert!(sample.iter().all(|id| pop.contains(id)));
+}
+
+#[test]
+fn record_spikes_of_assembly() {
+    use wheatnnleek::models::NeuronType;
+    use wheatnnleek::network::Network;
+    use wheatnnleek::populations::Assembly;
+    use wheatnnleek::Parameters;
+    let mut network = Network::new();
+    let params = Parameters::new();
+    let p1 = network.create(4, NeuronType::Izhikevich, &params).unwrap();
+    let p2 = network
+        .create(2, NeuronType::StaticPoisson, &params)
+        .unwrap();
+    let head = p1.slice(0..2).unwrap();
+    let assembly = Assembly::new(&[&head, &p1, &p2]);
+    assert_eq!(assembly.size(), 6);
+    assert_eq!(assembly.members().len(), 3);
+    network.record_spikes(&assembly).unwrap();
+    network.record_spikes(&head).unwrap();
+    assert_eq!(network.get_spike_records().len(), 6);
+    let registered = network.add_population(&assembly);
+    assert_eq!(registered.get_id(), 2);
+    assert_eq!(network.get_population_by_id(2).unwrap().size(), 6);
+}
+
+#[test]
+fn create_heterogeneous_population() {
+    use wheatnnleek::network::Network;
+    use wheatnnleek::parameters::{Distribution, ParameterSpec, ParameterValue};
+    let mut network = Network::new();
+    let mut spec = ParameterSpec::new();
+    spec.insert(
+        "v_th".to_string(),
+        ParameterValue::from(vec![10., 20., 30.]),
+    );
+    spec.insert(
+        "v_m".to_string(),
+        ParameterValue::from(Distribution::Uniform {
+            low: -70.,
+            high: -60.,
+        }),
+    );
+    let pop = network
+        .create_heterogeneous(3, "Izhikevich", &spec)
+        .unwrap();
+    assert_eq!(
+        network.get_property(&pop, "v_th".to_string()).unwrap(),
+        vec![10., 20., 30.]
+    );
+    for v in network.get_property(&pop, "v".to_string()).unwrap() {
+        assert!(v >= -70. && v < -60.);
+    }
+
+    spec.insert("a".to_string(), ParameterValue::from(vec![0.02]));
+    assert!(network
+        .create_heterogeneous(3, "Izhikevich", &spec)
+        .is_err());
+}
+
+#[test]
+fn parse_parameter_spec() {
+    use wheatnnleek::parameters::{Distribution, ParameterSpec, ParameterValue};
+    let spec: ParameterSpec = serde_json::from_str(
+        r#"{"a": 0.02, "c": [-65, -60], "d": {"distribution": "normal", "mean": 8, "std": 1}}"#,
+    )
+    .unwrap();
+    match spec["a"] {
+        ParameterValue::Constant(v) => assert_eq!(v, 0.02),
+        _ => panic!("expected a constant"),
+    }
+    match spec["c"] {
+        ParameterValue::Array(ref vs) => assert_eq!(vs, &vec![-65., -60.]),
+        _ => panic!("expected an array"),
+    }
+    match spec["d"] {
+        ParameterValue::Random(Distribution::Normal { mean, std }) => {
+            assert_eq!((mean, std), (8., 1.))
+        }
+        _ => panic!("expected a normal distribution"),
+    }
+}
+
+#[test]
+fn hh_model_params() {
+    use wheatnnleek::models::hodgkin_huxley::Model;
+    use wheatnnleek::models::Neuron;
+    use wheatnnleek::Parameters;
+    let mut params = Parameters::new();
+    params.insert("g_na".to_string(), 100.);
+    params.insert("cm".to_string(), 2.);
+    let mut model = Model::new(&params);
+    assert_eq!(model.g_na, 100.);
+    assert_eq!(model.cm, 2.);
+    assert_eq!(model.g_k, 36.);
+
+    let mut update = Parameters::new();
+    update.insert("e_k".to_string(), -80.);
+    model.set_params(&update).unwrap();
+    assert_eq!(model.e_k, -80.);
+    assert_eq!(model.g_na, 100.);
+    update.insert("tau_m".to_string(), 1.);
+    assert!(model.set_params(&update).is_err());
+}
+
+#[test]
+fn iaf_model_params() {
+    use wheatnnleek::models::iaf::Model;
+    use wheatnnleek::models::Neuron;
+    use wheatnnleek::Parameters;
+    let mut params = Parameters::new();
+    params.insert("r_m".to_string(), 20.);
+    params.insert("tau_m".to_string(), 5.);
+    let mut model = Model::new(&params);
+    assert_eq!(model.r_m, 20.);
+    assert_eq!(model.tau_m, 5.);
+    assert_eq!(model.e_l, -65.);
+    params.insert("v_m".to_string(), -60.);
+    assert_eq!(Model::new(&params).e_l, -60.);
+    params.insert("e_l".to_string(), -70.);
+    assert_eq!(Model::new(&params).e_l, -70.);
+
+    let mut update = Parameters::new();
+    update.insert("e_l".to_string(), -70.);
+    model.set_params(&update).unwrap();
+    assert_eq!(model.e_l, -70.);
+    update.insert("g_na".to_string(), 1.);
+    assert!(model.set_params(&update).is_err());
+}
+
+#[test]
+fn iz_model_params() {
+    use wheatnnleek::models::izhikevich::Model;
+    use wheatnnleek::models::Neuron;
+    use wheatnnleek::Parameters;
+    let mut model = Model::default();
+    let mut update = Parameters::new();
+    update.insert("a".to_string(), 0.1);
+    update.insert("d".to_string(), 2.);
+    model.set_params(&update).unwrap();
+    assert_eq!(model.a, 0.1);
+    assert_eq!(model.d, 2.);
+    update.insert("freq".to_string(), 1.);
+    assert!(model.set_params(&update).is_err());
+}
+
+#[test]
+fn static_poisson_model_params() {
+    use wheatnnleek::models::static_poisson::Model;
+    use wheatnnleek::models::Neuron;
+    use wheatnnleek::Parameters;
+    let mut model = Model::default();
+    let mut update = Parameters::new();
+    update.insert("freq".to_string(), 50.);
+    model.set_params(&update).unwrap();
+    assert_eq!(model.get_property("freq".to_string()).unwrap(), 50.);
+    model.set_params(&Parameters::new()).unwrap();
+    assert_eq!(model.get_property("freq".to_string()).unwrap(), 50.);
+    update.insert("v_m".to_string(), 1.);
+    assert!(model.set_params(&update).is_err());
+}
+
+#[test]
+fn cb_ath_lif_model_params() {
+    use wheatnnleek::models::cb_ath_lif::Model;
+    use wheatnnleek::models::Neuron;
+    use wheatnnleek::Parameters;
+    let mut params = Parameters::new();
+    params.insert("refact".to_string(), 2.);
+    params.insert("fix_theta".to_string(), 1.);
+    let mut model = Model::new(&params);
+    assert_eq!(model.refact, 2.);
+
+    let mut update = Parameters::new();
+    update.insert("tau_ge".to_string(), 3.);
+    update.insert("theta".to_string(), 0.);
+    model.set_params(&update).unwrap();
+    assert_eq!(model.tau_ge, 3.);
+    assert_eq!(model.theta, 0.);
+    update.insert("r_m".to_string(), 1.);
+    assert!(model.set_params(&update).is_err());
+}
+
+#[test]
+fn create_rejects_unknown_params() {
+    use wheatnnleek::models::NeuronType;
+    use wheatnnleek::network::Network;
+    use wheatnnleek::Parameters;
+    let mut network = Network::new();
+    let mut params = Parameters::new();
+    params.insert("tau_m".to_string(), 20.);
+    assert!(network.create(2, NeuronType::IAF, &params).is_ok());
+    assert!(network.create(2, NeuronType::Izhikevich, &params).is_err());
+    assert_eq!(network.get_population_by_id(0).unwrap().size(), 2);
+    assert!(network.get_population_by_id(99).is_err());
+}
+
+#[test]
+fn property_introspection() {
+    use wheatnnleek::models::NeuronType;
+    use wheatnnleek::network::Network;
+    use wheatnnleek::Parameters;
+    let mut network = Network::new();
+    let params = Parameters::new();
+    for ntype in &[
+        NeuronType::HodgkinHuxley,
+        NeuronType::IAF,
+        NeuronType::Izhikevich,
+        NeuronType::StaticPoisson,
+        NeuronType::ConductionBasedAdaptiveThresholdLIF,
+    ] {
+        let pop = network.create(1, *ntype, &params).unwrap();
+        let id = pop.get(0).unwrap() as usize;
+        for name in network.property_names(id).unwrap() {
+            network.set_property(&pop, name.clone(), 0.5).unwrap();
+            assert_eq!(network.get_property(&pop, name).unwrap(), vec![0.5]);
+        }
+        assert!(network.get_property(&pop, "foo".to_string()).is_err());
+        assert!(network.set_property(&pop, "foo".to_string(), 1.).is_err());
+    }
+    assert!(network.property_names(100).is_err());
+}
+
+#[test]
+fn set_properties_checks_length() {
+    use wheatnnleek::models::NeuronType;
+    use wheatnnleek::network::Network;
+    use wheatnnleek::populations::Assembly;
+    use wheatnnleek::Parameters;
+    let mut network = Network::new();
+    let pop = network
+        .create(2, NeuronType::Izhikevich, &Parameters::new())
+        .unwrap();
+    assert!(network
+        .set_properties(&pop, "u".to_string(), vec![1.])
+        .is_err());
+    network
+        .set_properties(&pop, "u".to_string(), vec![1., 2.])
+        .unwrap();
+    assert_eq!(
+        network.get_property(&pop, "u".to_string()).unwrap(),
+        vec![1., 2.]
+    );
+
+    // no neuron of a mixed assembly changes when one lacks the property
+    let iaf = network
+        .create(1, NeuronType::IAF, &Parameters::new())
+        .unwrap();
+    let mixed = Assembly::new(&[&pop, &iaf]);
+    assert!(network.set_property(&mixed, "u".to_string(), 5.).is_err());
+    assert_eq!(
+        network.get_property(&pop, "u".to_string()).unwrap(),
+        vec![1., 2.]
+    );
+    network
+        .set_properties(&mixed, "v".to_string(), vec![-50., -60., -70.])
+        .unwrap();
+
+    // nor when one rejects its value
+    let generators = network
+        .create_model(2, "spike_generator", &Parameters::new())
+        .unwrap();
+    network
+        .set_spike_times(&generators, &[vec![1.], vec![8.]])
+        .unwrap();
+    assert!(network
+        .set_property(&generators, "period".to_string(), 5.)
+        .is_err());
+    assert_eq!(
+        network
+            .get_property(&generators, "period".to_string())
+            .unwrap(),
+        vec![0., 0.]
+    );
+}
+
+#[test]
+fn register_neuron_model() {
+    use wheatnnleek::models::{iaf, registry, Neuron};
+    use wheatnnleek::network::Network;
+    use wheatnnleek::Parameters;
+
+    fn fast_iaf(params: &Parameters) -> Box<dyn Neuron> {
+        let mut model = iaf::Model::new(params);
+        model.tau_m = 1.;
+        Box::new(model)
+    }
+
+    registry::register("FastIAF", fast_iaf, iaf::Model::parameters()).unwrap();
+    assert!(registry::register("FastIAF", fast_iaf, Parameters::new()).is_err());
+    assert!(registry::names().contains(&"FastIAF".to_string()));
+    assert!(registry::is_registered("Izhikevich"));
+
+    let mut network = Network::new();
+    let mut params = Parameters::new();
+    params.insert("v_th".to_string(), -50.);
+    let pop = network.create_model(2, "FastIAF", &params).unwrap();
+    assert_eq!(
+        network.get_property(&pop, "tau_m".to_string()).unwrap(),
+        vec![1., 1.]
+    );
+    params.insert("a".to_string(), 1.);
+    assert!(network.create_model(2, "FastIAF", &params).is_err());
+    assert!(network
+        .create_model(2, "Unknown", &Parameters::new())
+        .is_err());
+}
+
+#[test]
+fn connect_from_spec() {
+    use wheatnnleek::connection_spec::ConnectionSpec;
+    use wheatnnleek::connections::{static_connection, PostSynapticEffect};
+    use wheatnnleek::connectors::array;
+    use wheatnnleek::models::NeuronType;
+    use wheatnnleek::network::Network;
+    use wheatnnleek::Parameters;
+    let mut network = Network::new();
+    let pre = network
+        .create(2, NeuronType::Izhikevich, &Parameters::new())
+        .unwrap();
+    let post = network
+        .create(2, NeuronType::Izhikevich, &Parameters::new())
+        .unwrap();
+
+    let spec: ConnectionSpec = serde_json::from_str(
+        r#"{"connector": {"type": "array", "array": "1001"},
+            "synapse": {"type": "static", "params": {"weight": 0.5, "delay": 2},
+                        "post_syn_effect": "Inhibitory"}}"#,
+    )
+    .unwrap();
+    let ids = network.connect_spec(&pre, &post, &spec).unwrap();
+    assert_eq!(ids.len(), 2);
+    let info = network.get_conn_info_by_id(ids[1]);
+    assert_eq!((info.source, info.target), (1, 3));
+    assert_eq!((info.weight, info.delay), (0.5, 2.));
+
+    let spec: ConnectionSpec = serde_json::from_str(
+        r#"{"connector": {"type": "linear"}, "synapse": {"type": "stdp"},
+            "weights": [0.1, 0.2]}"#,
+    )
+    .unwrap();
+    let ids = network.connect_spec(&pre, &post, &spec).unwrap();
+    assert_eq!(network.get_conn_info_by_id(ids[1]).weight, 0.2);
+
+    for bad in &[
+        r#"{"connector": {"type": "lineer"}, "synapse": {"type": "static"}}"#,
+        r#"{"connector": {"type": "linear"}, "synapse": {"type": "hebb"}}"#,
+        r#"{"connector": {"type": "linear", "p": 0.1}, "synapse": {"type": "static"}}"#,
+        r#"{"connector": {"type": "linear"}, "synapse": {"type": "static", "params": {"tau": 1}}}"#,
+        r#"{"connector": {"type": "array", "array": "100"}, "synapse": {"type": "static"}}"#,
+        r#"{"connector": {"type": "linear"}, "synapse": {"type": "static"}, "weights": [0.1]}"#,
+    ] {
+        let spec: ConnectionSpec = serde_json::from_str(bad).unwrap();
+        assert!(network.connect_spec(&pre, &post, &spec).is_err());
+    }
+    let syn =
+        static_connection::Connection::new(&Parameters::new(), PostSynapticEffect::Excitatory);
+    for (ar, weights) in &[("1001", vec![0.1]), ("100", vec![0.1])] {
+        assert!(network
+            .connect_with_initial_weights(
+                &pre,
+                &post,
+                weights.clone(),
+                &array::Connector::new(ar.as_bytes()),
+                &syn,
+            )
+            .is_err());
+    }
+    // failed specifications leave no connection behind
+    let spec: ConnectionSpec =
+        serde_json::from_str(r#"{"connector": {"type": "linear"}, "synapse": {"type": "static"}}"#)
+            .unwrap();
+    assert_eq!(
+        network.connect_spec(&pre, &post, &spec).unwrap(),
+        vec![4, 5]
+    );
+}
+
+#[test]
+fn adex_spike_frequency_adaptation() {
+    use wheatnnleek::models::adex::Model;
+    use wheatnnleek::models::{Integration, Neuron, NeuronActivity};
+    use wheatnnleek::network::Network;
+    use wheatnnleek::Parameters;
+    let mut params = Parameters::new();
+    params.insert("i_e".to_string(), 800.);
+    let mut counts = Vec::new();
+    for method in &[Integration::RungeKutta4, Integration::RungeKutta45] {
+        let mut model = Model::new(&params, wheatnnleek::models::adex::Synapse::Conductance);
+        model.set_integration(*method).unwrap();
+        let dt = Network::resolution();
+        let mut spikes = Vec::new();
+        let mut t = 0.;
+        while t < 1000. {
+            if let NeuronActivity::Fires(_) = model.update(t) {
+                spikes.push(t);
+            }
+            t += dt;
+        }
+        assert!(spikes.len() > 3);
+        assert!(model.v.is_finite() && model.w > 0.);
+        let first_isi = spikes[1] - spikes[0];
+        let last_isi = spikes[spikes.len() - 1] - spikes[spikes.len() - 2];
+        assert!(last_isi > first_isi);
+        counts.push(spikes.len());
+    }
+    assert!((counts[0] as i64 - counts[1] as i64).abs() <= 1);
+    assert!(Model::default()
+        .set_integration(Integration::Exact)
+        .is_err());
+}
+
+#[test]
+fn adex_synapses() {
+    use wheatnnleek::events::{Event, SpikeEvent};
+    use wheatnnleek::models::adex::{Model, Synapse};
+    use wheatnnleek::models::Neuron;
+    use wheatnnleek::Parameters;
+    for synapse in &[Synapse::Conductance, Synapse::Current] {
+        let mut model = Model::new(&Parameters::new(), *synapse);
+        let rest = model.v;
+        let mut event = SpikeEvent::new();
+        event.set_weight(-50.);
+        model.handle_spike(event);
+        assert_eq!(model.get_property("g_in".to_string()).unwrap(), 50.);
+        model.update(0.);
+        assert!(model.v < rest);
+    }
+}
+
+#[test]
+fn lif_psc_delta_jump() {
+    use wheatnnleek::events::{Event, SpikeEvent};
+    use wheatnnleek::models::lif::{Model, Synapse};
+    use wheatnnleek::models::Neuron;
+    use wheatnnleek::Parameters;
+    let mut model = Model::new(&Parameters::new(), Synapse::Delta);
+    let mut event = SpikeEvent::new();
+    event.set_weight(5.);
+    model.handle_spike(event);
+    model.update(0.);
+    assert!((model.v - (-65.)).abs() < 1e-9);
+}
+
+#[test]
+fn lif_psc_alpha_peak() {
+    use wheatnnleek::events::{Event, SpikeEvent};
+    use wheatnnleek::models::lif::{Model, Synapse};
+    use wheatnnleek::models::Neuron;
+    use wheatnnleek::network::Network;
+    use wheatnnleek::Parameters;
+    let mut model = Model::new(&Parameters::new(), Synapse::CurrentAlpha);
+    let mut event = SpikeEvent::new();
+    event.set_weight(100.);
+    model.handle_spike(event);
+    let dt = Network::resolution();
+    let mut peak: f64 = 0.;
+    let mut t = 0.;
+    while t < 10. {
+        model.update(t);
+        peak = peak.max(model.syn_ex);
+        t += dt;
+    }
+    assert!(peak <= 100. + 1e-9 && peak > 90.);
+}
+
+#[test]
+fn lif_cond_refractory() {
+    use wheatnnleek::models::lif::{Model, Synapse};
+    use wheatnnleek::models::{Neuron, NeuronActivity};
+    use wheatnnleek::network::Network;
+    use wheatnnleek::Parameters;
+    let mut params = Parameters::new();
+    params.insert("i_e".to_string(), 2000.);
+    params.insert("t_ref".to_string(), 5.);
+    let mut model = Model::new(&params, Synapse::ConductanceExp);
+    assert!((model.get_property("g_l".to_string()).unwrap() - 16.6667).abs() < 1e-9);
+    assert!(model.get_property("tau_m".to_string()).is_ok());
+    let dt = Network::resolution();
+    let mut spikes = Vec::new();
+    let mut t = 0.;
+    while t < 100. {
+        if let NeuronActivity::Fires(_) = model.update(t) {
+            spikes.push(t);
+        }
+        t += dt;
+    }
+    assert!(spikes.len() > 2);
+    for pair in spikes.windows(2) {
+        assert!(pair[1] - pair[0] >= 5.);
+    }
+}
+
+#[test]
+fn iaf_exact_integration_matches_analytic_solution() {
+    use wheatnnleek::models::iaf::Model;
+    use wheatnnleek::models::{Integration, Neuron};
+    use wheatnnleek::network::Network;
+    use wheatnnleek::Parameters;
+    let mut params = Parameters::new();
+    params.insert("i_e".to_string(), 0.9);
+    params.insert("v_th".to_string(), 0.);
+    let mut errors = Vec::new();
+    for method in &[Integration::Exact, Integration::RungeKutta4] {
+        let mut model = Model::new(&params);
+        model.set_integration(*method).unwrap();
+        // v(t) = v_inf + (v_0 - v_inf) exp(-t / tau_m)
+        let v_inf = model.e_l + model.r_m * 0.9;
+        let v_0 = model.v;
+        let mut t = 0.;
+        let mut error: f64 = 0.;
+        while t < 50. {
+            model.update(t);
+            t += Network::resolution();
+            let expected = v_inf + (v_0 - v_inf) * (-t / model.tau_m).exp();
+            error = error.max((model.v - expected).abs());
+        }
+        errors.push(error);
+    }
+    assert!(errors[0] < 1e-9);
+    assert!(errors[0] < errors[1]);
+}
+
+#[test]
+fn lif_psc_exp_exact_integration_matches_analytic_psp() {
+    use wheatnnleek::events::{Event, SpikeEvent};
+    use wheatnnleek::models::lif::{Model, Synapse};
+    use wheatnnleek::models::{Integration, Neuron};
+    use wheatnnleek::network::Network;
+    use wheatnnleek::Parameters;
+    let mut errors = Vec::new();
+    for method in &[Integration::Exact, Integration::RungeKutta4] {
+        let mut model = Model::new(&Parameters::new(), Synapse::CurrentExp);
+        model.set_integration(*method).unwrap();
+        let mut event = SpikeEvent::new();
+        event.set_weight(1000.);
+        model.handle_spike(event);
+        let (tau_m, tau_s, c_m, e_l) = (model.tau_m, model.tau_syn_ex, model.c_m, model.e_l);
+        let mut t = 0.;
+        let mut error: f64 = 0.;
+        while t < 50. {
+            model.update(t);
+            t += Network::resolution();
+            let expected = e_l
+                + 1000. / c_m * tau_m * tau_s / (tau_s - tau_m)
+                    * ((-t / tau_s).exp() - (-t / tau_m).exp());
+            error = error.max((model.v - expected).abs());
+        }
+        errors.push(error);
+    }
+    assert!(errors[0] < 1e-9);
+    assert!(errors[0] < errors[1]);
+}
+
+#[test]
+fn exact_integration_requires_linear_model() {
+    use wheatnnleek::models::lif::{Model, Synapse};
+    use wheatnnleek::models::{iaf, izhikevich, Integration, Neuron};
+    use wheatnnleek::Parameters;
+    let mut model = Model::new(&Parameters::new(), Synapse::ConductanceAlpha);
+    assert!(model.set_integration(Integration::Exact).is_err());
+    assert!(izhikevich::Model::default()
+        .set_integration(Integration::Exact)
+        .is_err());
+    // the linear models have no adaptive or exponential Euler variant
+    for method in &[Integration::RungeKutta45, Integration::ExponentialEuler] {
+        assert!(model.set_integration(*method).is_err());
+        assert!(iaf::Model::default().set_integration(*method).is_err());
+    }
+}
+
+#[test]
+fn ode_systems_match_analytic_solutions() {
+    use wheatnnleek::ode::{exponential_euler_system, rk45_system, rk4_system};
+    // harmonic oscillator x'' = -x, starting at x = 1, x' = 0
+    let oscillator = |y: &[f64], dydt: &mut [f64]| {
+        dydt[0] = y[1];
+        dydt[1] = -y[0];
+    };
+    let mut y = [1., 0.];
+    for _ in 0..100 {
+        rk4_system(oscillator, &mut y, 0.01);
+    }
+    assert!((y[0] - 1f64.cos()).abs() < 1e-9);
+    assert!((y[1] + 1f64.sin()).abs() < 1e-9);
+
+    let mut y = [1., 0.];
+    let mut step = 0.;
+    rk45_system(oscillator, &mut y, 5., &mut step, 1e-8);
+    assert!((y[0] - 5f64.cos()).abs() < 1e-6);
+    assert!((y[1] + 5f64.sin()).abs() < 1e-6);
+    assert!(step > 0. && step < 5.);
+
+    // y' = 2 - 0.5 y relaxes to 4 and is solved exactly
+    let mut y = [0.];
+    exponential_euler_system(
+        |_y: &[f64], a: &mut [f64], b: &mut [f64]| {
+            a[0] = 2.;
+            b[0] = 0.5;
+        },
+        &mut y,
+        3.,
+    );
+    assert!((y[0] - 4. * (1. - (-1.5f64).exp())).abs() < 1e-12);
+}
+
+#[test]
+fn hh_integration_methods() {
+    use wheatnnleek::models::hodgkin_huxley::Model;
+    use wheatnnleek::models::{Integration, Neuron, NeuronActivity};
+    use wheatnnleek::network::Network;
+    use wheatnnleek::Parameters;
+    let mut params = Parameters::new();
+    params.insert("i_e".to_string(), 10.);
+    let mut upstrokes = Vec::new();
+    for method in &[Integration::RungeKutta45, Integration::ExponentialEuler] {
+        let mut model = Model::new(&params);
+        model.set_integration(*method).unwrap();
+        let mut t = 0.;
+        let mut spikes = 0;
+        let mut upstroke = 0;
+        while t < 100. {
+            let v = model.v;
+            if let NeuronActivity::Fires(_) = model.update(t) {
+                spikes += 1;
+            }
+            if v < 0. && model.v >= 0. {
+                upstroke += 1;
+            }
+            t += Network::resolution();
+        }
+        assert!(model.v.is_finite());
+        assert!(model.m >= 0. && model.m <= 1.);
+        assert!(model.h >= 0. && model.h <= 1.);
+        assert!(model.n >= 0. && model.n <= 1.);
+        // spikes are detected on the falling side above 0 mV, which coarse
+        // steps can skip, so there are at most as many as upstrokes
+        assert!(upstroke > 0);
+        assert!(spikes <= upstroke);
+        upstrokes.push(upstroke);
+    }
+    // about 7 spikes in 100 ms for this input
+    assert!(upstrokes[0] >= 5 && upstrokes[0] <= 8);
+    assert!(Model::default()
+        .set_integration(Integration::Exact)
+        .is_err());
+}
+
+#[test]
+fn iz_integration_methods_agree() {
+    use wheatnnleek::models::izhikevich::Model;
+    use wheatnnleek::models::{Integration, Neuron, NeuronActivity};
+    use wheatnnleek::network::Network;
+    use wheatnnleek::Parameters;
+    let mut params = Parameters::new();
+    params.insert("i_e".to_string(), 10.);
+    let mut first_spikes = Vec::new();
+    for method in &[Integration::RungeKutta4, Integration::RungeKutta45] {
+        let mut model = Model::new(&params);
+        model.set_integration(*method).unwrap();
+        let mut t = 0.;
+        let mut spikes = Vec::new();
+        while t < 200. {
+            if let NeuronActivity::Fires(_) = model.update(t) {
+                spikes.push(t);
+            }
+            assert!(model.v.is_finite() && model.u.is_finite());
+            t += Network::resolution();
+        }
+        assert!(spikes.len() > 1);
+        first_spikes.push(spikes[0]);
+    }
+    // the fixed steps overshoot the peak more than the adaptive ones, which
+    // end there, so only the first spike is compared
+    assert!((first_spikes[0] - first_spikes[1]).abs() <= Network::resolution());
+
+    // below the peak, the dynamics do not depend on it
+    let mut low_peak = Model::new(&params);
+    low_peak.set_property("v_th".to_string(), -40.).unwrap();
+    let mut model = Model::new(&params);
+    let mut t = 0.;
+    while model.v < -40. {
+        low_peak.update(t);
+        model.update(t);
+        if model.v < -40. {
+            assert_eq!(model.v, low_peak.v);
+        }
+        t += Network::resolution();
+    }
+}
+
+#[test]
+fn precise_spike_timing() {
+    use wheatnnleek::models::lif::{Model, Synapse};
+    use wheatnnleek::models::Neuron;
+    use wheatnnleek::network::Network;
+    use wheatnnleek::Parameters;
+    let mut params = Parameters::new();
+    params.insert("i_e".to_string(), 500.);
+    // v relaxes towards -50 mV and crosses -55 mV at tau_m * ln(4)
+    let expected = 10. * 4f64.ln();
+    let mut errors = Vec::new();
+    for &precise in &[true, false] {
+        let mut model = Model::new(&params, Synapse::CurrentExp);
+        model.set_precise_timing(precise).unwrap();
+        model.new_spike_record();
+        model.set_spike_recording(true);
+        let mut t = 0.;
+        while t < 20. {
+            model.update(t);
+            t += Network::resolution();
+        }
+        let first = model.get_spike_records()[0][0];
+        errors.push((first - expected).abs());
+    }
+    assert!(errors[0] < 0.05);
+    assert!(errors[0] < errors[1]);
+
+    let mut network = Network::new();
+    network.set_precise_timing(true).unwrap();
+    let pop = network.create_model(1, "iaf_psc_exp", &params).unwrap();
+    network.record_spikes(&pop).unwrap();
+    network.run_for(20.);
+    let (_, records) = &network.get_spike_records()[0];
+    assert!((records[0][0] - expected).abs() < 0.05);
+
+    // models that only spike at the beginning of a step refuse precise timing
+    for model in &["IAF", "ConductionBasedAdaptiveThresholdLIF", "iaf_cond_exp"] {
+        assert!(network.create_model(1, model, &Parameters::new()).is_err());
+    }
+    let mut network = Network::new();
+    network
+        .create_model(1, "iaf_psc_exp", &Parameters::new())
+        .unwrap();
+    network.create_model(1, "IAF", &Parameters::new()).unwrap();
+    assert!(network.set_precise_timing(true).is_err());
+    assert!(!network.precise_timing());
+}
+
+#[test]
+fn stdp_trace_decay_follows_precise_timing() {
+    use wheatnnleek::connections::stdp_connection::Connection;
+    use wheatnnleek::connections::Connection as Conn;
+    use wheatnnleek::network::Network;
+    use wheatnnleek::ode::rk4;
+    let dt = Network::resolution();
+    let mut weights = Vec::new();
+    for &precise in &[false, true] {
+        let mut conn = Connection::default();
+        conn.set_weight(0.5);
+        conn.set_precise_timing(precise);
+        conn.on_post_spike(1.);
+        conn.on_pre_spike(3.3);
+        weights.push(conn.weight());
+    }
+    // without precise timing the trace decays over whole ms, each by one
+    // step of the resolution, as it always has
+    let trace = 1. + 2. * rk4(|y| -y / 20., 1., dt);
+    assert!((weights[0] - (0.5 - 0.0001 * trace)).abs() < 1e-12);
+    let trace = (-2.3f64 / 20.).exp();
+    assert!((weights[1] - (0.5 - 0.0001 * trace)).abs() < 1e-12);
+}
+
+#[test]
+fn precise_input_offset() {
+    use wheatnnleek::events::{Event, SpikeEvent};
+    use wheatnnleek::models::lif::{Model, Synapse};
+    use wheatnnleek::models::Neuron;
+    use wheatnnleek::network::Network;
+    use wheatnnleek::Parameters;
+    let mut model = Model::new(&Parameters::new(), Synapse::Delta);
+    model.set_precise_timing(true).unwrap();
+    let dt = Network::resolution();
+    let mut event = SpikeEvent::new();
+    event.set_weight(5.);
+    event.set_offset(0.4 * dt);
+    model.handle_spike(event);
+    model.update(0.);
+    // the jump decays only over the part of the step after its arrival
+    let expected = -70. + 5. * (-0.6 * dt / 10.).exp();
+    assert!((model.v - expected).abs() < 1e-9);
+}
+
+#[test]
+fn spike_generator_trains() {
+    use wheatnnleek::models::spike_generator::Model;
+    use wheatnnleek::models::{Neuron, NeuronActivity};
+    use wheatnnleek::network::Network;
+    use wheatnnleek::Parameters;
+    let mut params = Parameters::new();
+    params.insert("period".to_string(), 10.);
+    let mut model = Model::new(&params);
+    assert!(model.set_spike_times(&[2., 12.]).is_err());
+    model.set_spike_times(&[5., 2., 2.]).unwrap();
+    model.new_spike_record();
+    model.set_spike_recording(true);
+    let dt = Network::resolution();
+    let mut t = 0.;
+    let mut spikes = 0.;
+    while t < 30. - dt / 2. {
+        if let NeuronActivity::Fires(event) = model.update(t) {
+            spikes += event.multiplicity();
+        }
+        t += dt;
+    }
+    assert_eq!(spikes, 9.);
+    let records = &model.get_spike_records()[0];
+    assert_eq!(records.len(), 6);
+    for (time, expected) in records.iter().zip(&[2., 5., 12., 15., 22., 25.]) {
+        assert!(time <= expected && expected - time < dt);
+    }
+}
+
+#[test]
+fn replay_spike_trains() {
+    use std::io::Write;
+    use wheatnnleek::models::spike_generator::read_spike_times;
+    use wheatnnleek::network::Network;
+    use wheatnnleek::Parameters;
+    let mut network = Network::new();
+    let generators = network
+        .create_model(2, "spike_generator", &Parameters::new())
+        .unwrap();
+    let path = std::env::temp_dir().join("wheatnnleek_replay_spike_trains.txt");
+    let mut file = std::fs::File::create(&path).unwrap();
+    writeln!(file, "# index time").unwrap();
+    writeln!(file, "1 3.0\n0 1.0\n1 7.0").unwrap();
+    network
+        .load_spike_times(&generators, path.to_str().unwrap())
+        .unwrap();
+    network.record_spikes(&generators).unwrap();
+    network.run_for(10.);
+    let records = network.get_spike_records();
+    assert_eq!(records[0].1[0].len(), 1);
+    assert_eq!(records[1].1[0].len(), 2);
+
+    writeln!(file, "2 1.0").unwrap();
+    assert!(network
+        .load_spike_times(&generators, path.to_str().unwrap())
+        .is_err());
+    let mut file = std::fs::File::create(&path).unwrap();
+    writeln!(file, "0 1.0\n1 NaN").unwrap();
+    let error = read_spike_times(path.to_str().unwrap(), 2).unwrap_err();
+    assert!(error.ends_with(":2: invalid time NaN"));
+    std::fs::remove_file(&path).unwrap();
+
+    let neurons = network
+        .create_model(1, "iaf_psc_delta", &Parameters::new())
+        .unwrap();
+    assert!(network.set_spike_times(&neurons, &[vec![1.]]).is_err());
+    assert!(network.set_spike_times(&generators, &[vec![1.]]).is_err());
+}
+
+#[test]
+fn rate_schedules() {
+    use wheatnnleek::models::inhomogeneous_poisson::RateSchedule;
+    let steps: RateSchedule =
+        serde_json::from_str(r#"{"schedule": "steps", "times": [10, 20], "rates": [5, 0]}"#)
+            .unwrap();
+    assert_eq!(steps.rate(5.), 0.);
+    assert_eq!(steps.rate(10.), 5.);
+    assert_eq!(steps.rate(25.), 0.);
+    let sinusoid = RateSchedule::Sinusoid {
+        mean: 10.,
+        amplitude: 20.,
+        frequency: 10.,
+        phase: 0.,
+    };
+    assert!((sinusoid.rate(25.) - 30.).abs() < 1e-9);
+    assert_eq!(sinusoid.rate(75.), 0.);
+    let sampled: RateSchedule =
+        serde_json::from_str(r#"{"schedule": "sampled", "interval": 2, "rates": [0, 4, 8]}"#)
+            .unwrap();
+    assert_eq!(sampled.rate(1.), 2.);
+    assert_eq!(sampled.rate(4.), 8.);
+    assert_eq!(sampled.rate(5.), 0.);
+    let invalid = RateSchedule::Steps {
+        times: vec![2., 1.],
+        rates: vec![1., 1.],
+    };
+    assert!(invalid.validate().is_err());
+}
+
+#[test]
+fn inhomogeneous_poisson_input() {
+    use wheatnnleek::models::inhomogeneous_poisson::RateSchedule;
+    use wheatnnleek::models::NeuronType;
+    use wheatnnleek::network::Network;
+    use wheatnnleek::Parameters;
+    let mut network = Network::new();
+    let inputs = network
+        .create_model(2, "inhomogeneous_poisson_generator", &Parameters::new())
+        .unwrap();
+    let targets = network
+        .create_model(2, "spike_generator", &Parameters::new())
+        .unwrap();
+    // rates high enough to fire at every step
+    let schedule = RateSchedule::Steps {
+        times: vec![10., 20.],
+        rates: vec![1e4, 0.],
+    };
+    network.set_rate_schedule(&inputs, &schedule).unwrap();
+    assert!(network.set_rate_schedule(&targets, &schedule).is_err());
+    network.run_for(30.);
+    assert_eq!(
+        network.get_property(&inputs, "freq".to_string()).unwrap(),
+        vec![0., 0.]
+    );
+
+    network.set_rates(&inputs, vec![5., 7.]).unwrap();
+    assert_eq!(
+        network.get_property(&inputs, "freq".to_string()).unwrap(),
+        vec![5., 7.]
+    );
+    assert!(network.set_rates(&inputs, vec![5.]).is_err());
+    assert!(network.set_rates(&inputs, vec![5., -1.]).is_err());
+
+    let poisson = network
+        .create(3, NeuronType::StaticPoisson, &Parameters::new())
+        .unwrap();
+    network.set_rates(&poisson, vec![1., 2., 3.]).unwrap();
+    assert_eq!(
+        network.get_property(&poisson, "freq".to_string()).unwrap(),
+        vec![1., 2., 3.]
+    );
+}
+
+#[test]
+fn dc_current_injection() {
+    use wheatnnleek::devices::CurrentSource;
+    use wheatnnleek::network::Network;
+    use wheatnnleek::Parameters;
+    let mut network = Network::new();
+    let pop = network
+        .create_model(2, "iaf_psc_exp", &Parameters::new())
+        .unwrap();
+    let source: CurrentSource =
+        serde_json::from_str(r#"{"type": "dc", "amplitude": 200, "start": 10, "stop": 1000}"#)
+            .unwrap();
+    let part = pop.slice(0..1).unwrap();
+    network.connect_current(source, &part).unwrap();
+    network.run_for(5.);
+    assert_eq!(
+        network.get_property(&pop, "v".to_string()).unwrap(),
+        vec![-70., -70.]
+    );
+    network.run_for(100.);
+    let v = network.get_property(&pop, "v".to_string()).unwrap();
+    // v approaches e_l + i * tau_m / c_m = -62 mV
+    assert!((v[0] + 62.).abs() < 0.1);
+    assert_eq!(v[1], -70.);
+
+    let generators = network
+        .create_model(1, "spike_generator", &Parameters::new())
+        .unwrap();
+    let source: CurrentSource = serde_json::from_str(r#"{"type": "dc", "amplitude": 1}"#).unwrap();
+    assert!(network.connect_current(source, &generators).is_err());
+}
+
+#[test]
+fn current_waveforms() {
+    use wheatnnleek::devices::{CurrentGenerator, CurrentSource, Waveform};
+    let steps = CurrentSource::new(Waveform::Steps {
+        times: vec![1., 2.],
+        amplitudes: vec![3., 4.],
+    });
+    let mut generator = CurrentGenerator::new(steps, vec![0, 2]).unwrap();
+    let mut currents = vec![0.; 3];
+    generator.inject(1.5, 0.1, &mut currents);
+    assert_eq!(currents, vec![3., 0., 3.]);
+
+    let ac = CurrentSource::new(Waveform::Ac {
+        amplitude: 2.,
+        frequency: 50.,
+        phase: 0.,
+        offset: 1.,
+    });
+    let mut generator = CurrentGenerator::new(ac, vec![0]).unwrap();
+    let mut currents = vec![0.];
+    generator.inject(5., 0.1, &mut currents);
+    assert!((currents[0] - 3.).abs() < 1e-9);
+
+    let mut ou = CurrentSource::new(Waveform::OrnsteinUhlenbeck {
+        mean: 5.,
+        std: 2.,
+        tau: 1.,
+    });
+    ou.stop = Some(1000.);
+    let mut generator = CurrentGenerator::new(ou, (0..1000).collect()).unwrap();
+    let mut currents = vec![0.; 1000];
+    for _ in 0..20 {
+        currents.iter_mut().for_each(|c| *c = 0.);
+        generator.inject(0., 0.5, &mut currents);
+    }
+    let mean = currents.iter().sum::<f64>() / 1000.;
+    let var = currents
+        .iter()
+        .map(|c| (c - mean) * (c - mean))
+        .sum::<f64>()
+        / 1000.;
+    assert!((mean - 5.).abs() < 0.5);
+    assert!((var.sqrt() - 2.).abs() < 0.5);
+
+    let invalid = CurrentSource::new(Waveform::OrnsteinUhlenbeck {
+        mean: 0.,
+        std: 1.,
+        tau: 0.,
+    });
+    assert!(CurrentGenerator::new(invalid, vec![0]).is_err());
+}
+
+#[test]
+fn spike_encoders() {
+    use wheatnnleek::encoders::*;
+    use wheatnnleek::network::Network;
+    let latency = LatencyEncoder::new(0., 1.);
+    let trains = latency.encode(&[1., 0.5, 0.], 10., 20.);
+    assert_eq!(trains, vec![vec![10.], vec![20.], vec![]]);
+    // values just above the threshold still fire within the window
+    let trains = latency.encode(&[f64::NAN, 1e-300], 10., 20.);
+    assert_eq!(trains, vec![vec![], vec![30. - Network::resolution()]]);
+
+    let rank_order = RankOrderEncoder::new(0.);
+    let trains = rank_order.encode(&[0.2, 0.9, 0., 0.2], 0., 30.);
+    assert_eq!(trains, vec![vec![10.], vec![0.], vec![], vec![10.]]);
+    let trains = rank_order.encode(&[0.2, f64::NAN, f64::INFINITY, 0.9], 0., 30.);
+    assert_eq!(trains, vec![vec![15.], vec![], vec![], vec![0.]]);
+
+    let population = PopulationEncoder::new(5, 0., 1.);
+    assert_eq!(population.neurons(2), 10);
+    let trains = population.encode(&[0.5, 0.8], 0., 10.);
+    assert_eq!(trains.len(), 10);
+    // the field centered nearest to each value fires first
+    let first = |trains: &[Vec<f64>]| {
+        (0..trains.len())
+            .filter(|&i| !trains[i].is_empty())
+            .min_by(|&a, &b| trains[a][0].partial_cmp(&trains[b][0]).unwrap())
+            .unwrap()
+    };
+    assert_eq!(first(&trains[..5]), 2);
+    assert_eq!(first(&trains[5..]), 3);
+    assert_eq!(trains[..5].iter().filter(|t| t.is_empty()).count(), 2);
+    assert!(population
+        .encode(&[f64::NAN], 0., 10.)
+        .iter()
+        .all(|t| t.is_empty()));
+    assert!(trains.iter().flatten().all(|&t| t >= 0. && t < 10.));
+
+    let coding: Coding =
+        serde_json::from_str(r#"{"coding": "population", "fields": 5, "min": 0, "max": 1}"#)
+            .unwrap();
+    assert_eq!(coding.encode(&[0.5, 0.8], 0., 10.), trains);
+
+    let rate = RateEncoder::new(0., 255., 100.);
+    assert_eq!(rate.rates(&[0., 255., 510.]), vec![0., 100., 100.]);
+    assert_eq!(rate.rates(&[f64::NAN, f64::INFINITY]), vec![0., 0.]);
+    assert!(rate.encode(&[f64::NAN], 0., 100.)[0].is_empty());
+    let trains = rate.encode(&[0., 255.], 100., 1000.);
+    assert!(trains[0].is_empty());
+    assert!(trains[1].len() > 60 && trains[1].len() < 140);
+    assert!(trains[1].iter().all(|&t| t >= 100. && t < 1100.));
+}
+
+#[test]
+fn encode_presentation_windows() {
+    use wheatnnleek::encoders::LatencyEncoder;
+    use wheatnnleek::network::Network;
+    use wheatnnleek::Parameters;
+    let mut network = Network::new();
+    let inputs = network
+        .create_model(3, "spike_generator", &Parameters::new())
+        .unwrap();
+    network.record_spikes(&inputs).unwrap();
+    let encoder = LatencyEncoder::new(0., 1.);
+    assert!(network.encode(&inputs, &encoder, &[1., 0.], 20.).is_err());
+
+    for _ in 0..2 {
+        network
+            .encode(&inputs, &encoder, &[1., 0.5, 0.], 20.)
+            .unwrap();
+        network.run_for(20.);
+    }
+    assert_eq!(network.current_time(), 40.);
+    let records = network.get_spike_records();
+    assert_eq!(records[0].1, vec![vec![0.], vec![20.]]);
+    assert_eq!(records[1].1.iter().map(|r| r.len()).sum::<usize>(), 2);
+    assert!(records[2].1.iter().all(|r| r.is_empty()));
+
+    // run keeps its duration of t / resolution ms
+    let reached = network.run(10.);
+    assert!((reached - 40. - 10. / Network::resolution()).abs() < 1e-9);
+}
+
+#[test]
+fn idx_parsing() {
+    use wheatnnleek::datasets::{Dataset, Idx};
+    let mut images = vec![0, 0, 0x08, 3, 0, 0, 0, 2, 0, 0, 0, 2, 0, 0, 0, 2];
+    images.extend(&[0, 255, 128, 1, 7, 7, 7, 7]);
+    let idx = Idx::parse(&images).unwrap();
+    assert_eq!(idx.dims, vec![2, 2, 2]);
+    assert_eq!(idx.len(), 2);
+    assert_eq!(idx.sample_size(), 4);
+    assert_eq!(idx.sample(0), &[0., 255., 128., 1.]);
+    assert!(Idx::parse(&images[..images.len() - 1]).is_err());
+    assert!(Idx::parse(&[0, 0, 0x0A, 1, 0, 0, 0, 0]).is_err());
+    // dimensions whose product overflows
+    let mut huge = vec![0, 0, 0x08, 3];
+    huge.extend(&[0xFF; 12]);
+    assert_eq!(Idx::parse(&huge).unwrap_err(), "IDX dimensions overflow");
+
+    let mut doubles = vec![0, 0, 0x0E, 1, 0, 0, 0, 1];
+    doubles.extend(&(-1.5f64).to_be_bytes());
+    assert_eq!(Idx::parse(&doubles).unwrap().values, vec![-1.5]);
+    let ints = [0, 0, 0x0C, 1, 0, 0, 0, 1, 0xFF, 0xFF, 0xFF, 0xFE];
+    assert_eq!(Idx::parse(&ints).unwrap().values, vec![-2.]);
+
+    let dir = std::env::temp_dir();
+    let images_path = dir.join("wheatnnleek_idx_images");
+    let labels_path = dir.join("wheatnnleek_idx_labels");
+    std::fs::write(&images_path, &images).unwrap();
+    std::fs::write(&labels_path, &[0, 0, 0x08, 1, 0, 0, 0, 2, 3, 1]).unwrap();
+    let read = |classes| {
+        Dataset::read(
+            images_path.to_str().unwrap(),
+            labels_path.to_str().unwrap(),
+            classes,
+        )
+    };
+    let dataset = read(4).unwrap();
+    assert_eq!(dataset.labels, vec![3, 1]);
+    assert_eq!(dataset.classes(), 4);
+    assert!(read(3).is_err());
+    std::fs::write(&labels_path, &[0, 0, 0x08, 1, 0, 0, 0, 1, 3]).unwrap();
+    assert!(read(4).is_err());
+    std::fs::remove_file(&images_path).unwrap();
+    std::fs::remove_file(&labels_path).unwrap();
+}
+
+#[test]
+fn presentation_harness() {
+    use wheatnnleek::connections::{static_connection, PostSynapticEffect};
+    use wheatnnleek::connectors::all_to_all;
+    use wheatnnleek::datasets::{Idx, Presentation};
+    use wheatnnleek::models::NeuronType;
+    use wheatnnleek::network::Network;
+    use wheatnnleek::Parameters;
+    let mut network = Network::new();
+    let input = network
+        .create(4, NeuronType::StaticPoisson, &Parameters::new())
+        .unwrap();
+    let output = network
+        .create_model(2, "iaf_psc_delta", &Parameters::new())
+        .unwrap();
+    let mut params = Parameters::new();
+    params.insert("weight".to_string(), 20.);
+    network.connect(
+        &input,
+        &output,
+        &all_to_all::Connector::default(),
+        &static_connection::Connection::new(&params, PostSynapticEffect::Excitatory),
+    );
+    network.record_spikes(&output).unwrap();
+
+    // without intensity the first presentations cannot make the output fire
+    let presentation = Presentation {
+        duration: 100.,
+        rest: 40.,
+        scale: 1.,
+        intensity: 0.,
+        intensity_step: 5.,
+        min_spikes: 5,
+        max_retries: 10,
+    };
+    let response = presentation
+        .present(&mut network, &input, &output, &[10., 10., 0., 0.])
+        .unwrap();
+    assert!(response.attempts > 1);
+    assert_eq!(response.intensity, 5. * (response.attempts - 1) as f64);
+    assert!(response.total() >= 5);
+    assert_eq!(network.spike_counts(&output), response.counts);
+    presentation.rest(&mut network, &input).unwrap();
+    assert_eq!(
+        network.current_time(),
+        100. * response.attempts as f64 + 40.
+    );
+
+    let idx = Idx {
+        dims: vec![3, 4],
+        values: vec![20.; 12],
+    };
+    let responses = presentation
+        .present_dataset(&mut network, &input, &output, &idx, 2)
+        .unwrap();
+    assert_eq!(responses.len(), 2);
+    let wrong = Idx {
+        dims: vec![1, 3],
+        values: vec![0.; 3],
+    };
+    assert!(presentation
+        .present_dataset(&mut network, &input, &output, &wrong, 1)
+        .is_err());
+}
+
+#[test]
+fn spike_count_readout() {
+    use wheatnnleek::readout::*;
+    // neuron 0 prefers class 0, neuron 1 class 1 and neuron 2 never fires
+    let counts = vec![
+        vec![6, 1, 0],
+        vec![4, 0, 0],
+        vec![1, 5, 0],
+        vec![0, 3, 0],
+        vec![2, 3, 0],
+    ];
+    let labels = vec![0, 0, 1, 1, 1];
+    let neuron_labels = label_neurons(&counts, &labels, 2).unwrap();
+    assert_eq!(neuron_labels, vec![Some(0), Some(1), None]);
+    assert!(label_neurons(&counts, &labels[..4], 2).is_err());
+    assert!(label_neurons(&counts, &[0, 0, 1, 1, 2], 2).is_err());
+
+    assert_eq!(
+        vote(&[2, 3, 9], &neuron_labels, 2, Voting::Average),
+        Ok(Some(1))
+    );
+    assert_eq!(
+        vote(&[5, 1, 0], &neuron_labels, 2, Voting::Majority),
+        Ok(Some(0))
+    );
+    assert_eq!(
+        vote(&[0, 0, 9], &neuron_labels, 2, Voting::Majority),
+        Ok(None)
+    );
+    let labels_3 = vec![Some(0), Some(0), Some(1)];
+    // two neurons of class 0 fire once, one neuron of class 1 fires three times
+    assert_eq!(
+        vote(&[1, 1, 3], &labels_3, 2, Voting::Majority),
+        Ok(Some(0))
+    );
+    assert_eq!(vote(&[1, 1, 3], &labels_3, 2, Voting::Average), Ok(Some(1)));
+    assert!(vote(&[1, 1, 3], &[Some(0), Some(2), None], 2, Voting::Average).is_err());
+
+    let mut readout = LinearReadout::new(3, 2);
+    assert!(readout.train(&counts, &labels[..4]).is_err());
+    assert!(readout.train(&counts, &[0, 0, 1, 1, 2]).is_err());
+    readout.train(&counts, &labels).unwrap();
+    let predicted: Vec<Option<usize>> = counts.iter().map(|c| Some(readout.predict(c))).collect();
+    let matrix = ConfusionMatrix::from_predictions(&labels, &predicted, 2);
+    assert_eq!(matrix.accuracy(), 1.);
+
+    let mut matrix = ConfusionMatrix::new(2);
+    matrix.add(0, Some(0));
+    matrix.add(0, Some(1));
+    matrix.add(1, Some(1));
+    matrix.add(1, None);
+    assert_eq!(matrix.counts, vec![vec![1, 1], vec![0, 1]]);
+    assert_eq!(matrix.total(), 4);
+    assert_eq!(matrix.accuracy(), 0.5);
+    assert_eq!(matrix.recall(0), 0.5);
+    assert_eq!(matrix.recall(1), 0.5);
+}
+
+#[test]
+fn weight_normalization() {
+    use wheatnnleek::connections::{static_connection, PostSynapticEffect};
+    use wheatnnleek::connectors::all_to_all;
+    use wheatnnleek::models::NeuronType;
+    use wheatnnleek::network::{Network, WeightNormalization};
+    use wheatnnleek::Parameters;
+    let mut network = Network::new();
+    let input = network
+        .create(3, NeuronType::StaticPoisson, &Parameters::new())
+        .unwrap();
+    let output = network
+        .create_model(2, "iaf_psc_delta", &Parameters::new())
+        .unwrap();
+    let mut params = Parameters::new();
+    params.insert("weight".to_string(), 1.);
+    let connection = static_connection::Connection::new(&params, PostSynapticEffect::Excitatory);
+    let ids = network.connect(
+        &input,
+        &output,
+        &all_to_all::Connector::default(),
+        &connection,
+    );
+    let other = network.connect(
+        &output,
+        &output,
+        &all_to_all::Connector::default(),
+        &connection,
+    );
+    let sums = |network: &Network, ids: &[usize]| {
+        let mut sums = vec![0.; 2];
+        for &id in ids {
+            let info = network.get_conn_info_by_id(id);
+            sums[info.target as usize - 3] += info.weight;
+        }
+        sums
+    };
+
+    network.set_weight_by_conn_id(ids[0], 4.);
+    network.normalize_incoming_weights(&ids, 1.5).unwrap();
+    let s = sums(&network, &ids);
+    assert!((s[0] - 1.5).abs() < 1e-12 && (s[1] - 1.5).abs() < 1e-12);
+    assert!((network.get_conn_info_by_id(ids[0]).weight - 1.).abs() < 1e-12);
+    assert_eq!(sums(&network, &other), vec![2., 2.]);
+    assert!(network.normalize_incoming_weights(&[99], 1.).is_err());
+    assert!(network.normalize_incoming_weights(&ids, -1.).is_err());
+
+    network.normalize_population_inputs(&output, 6.).unwrap();
+    let all: Vec<usize> = ids.iter().chain(&other).cloned().collect();
+    assert_eq!(sums(&network, &all), vec![6., 6.]);
+
+    // scheduled normalizations apply during runs
+    let normalization = WeightNormalization {
+        connections: ids.clone(),
+        target_sum: 3.,
+        interval: Some(10.),
+    };
+    network.add_weight_normalization(normalization).unwrap();
+    network.run_for(5.);
+    network.set_weight_by_conn_id(ids[0], 10.);
+    network.run_for(4.);
+    assert_eq!(network.get_conn_info_by_id(ids[0]).weight, 10.);
+    network.run_for(1.);
+    let s = sums(&network, &ids);
+    assert!((s[0] - 3.).abs() < 1e-12);
+
+    network.clear_weight_normalizations();
+    let after_run: WeightNormalization =
+        serde_json::from_str(&format!(r#"{{"connections": {:?}, "target_sum": 2}}"#, ids)).unwrap();
+    network.add_weight_normalization(after_run).unwrap();
+    network.set_weight_by_conn_id(ids[1], 10.);
+    network.run_for(1.);
+    let s = sums(&network, &ids);
+    assert!((s[0] - 2.).abs() < 1e-12 && (s[1] - 2.).abs() < 1e-12);
+    let bad = WeightNormalization {
+        connections: ids,
+        target_sum: 1.,
+        interval: Some(0.),
+    };
+    assert!(network.add_weight_normalization(bad).is_err());
+}
+
+#[test]
+fn adaptive_threshold() {
+    use wheatnnleek::homeostasis::ThresholdAdaptation;
+    use wheatnnleek::models::{izhikevich, NeuronActivity};
+    use wheatnnleek::network::Network;
+    use wheatnnleek::Parameters;
+    let mut network = Network::new();
+    let mut params = Parameters::new();
+    params.insert("i_e".to_string(), 800.);
+    let plain = network.create_model(1, "AdEx", &params).unwrap();
+    let adaptive = network.create_model(1, "AdEx", &params).unwrap();
+    let lif = network.create_model(1, "iaf_psc_exp", &params).unwrap();
+    let hh = network
+        .create_model(1, "HodgkinHuxley", &Parameters::new())
+        .unwrap();
+    let adaptation = ThresholdAdaptation::new(2., 100.);
+    assert!(network.add_threshold_adaptation(&hh, &adaptation).is_err());
+    assert_eq!(
+        network
+            .property_names(hh.get(0).unwrap() as usize)
+            .unwrap()
+            .len(),
+        12
+    );
+    network
+        .add_threshold_adaptation(&adaptive, &adaptation)
+        .unwrap();
+    network.add_threshold_adaptation(&lif, &adaptation).unwrap();
+    let v_t = network.get_property(&plain, "v_t".to_string()).unwrap();
+    assert_eq!(
+        network.get_property(&adaptive, "v_t".to_string()).unwrap(),
+        v_t
+    );
+
+    for pop in &[&plain, &adaptive, &lif] {
+        network.record_spikes(*pop).unwrap();
+    }
+    network.run_for(500.);
+    let counts = |network: &Network| {
+        [&plain, &adaptive, &lif]
+            .iter()
+            .map(|p| network.spike_counts(*p)[0])
+            .collect::<Vec<usize>>()
+    };
+    let first = counts(&network);
+    assert!(first[1] > 0 && first[1] < first[0]);
+    assert!(first[2] > 0);
+    let theta = network
+        .get_property(&adaptive, "theta".to_string())
+        .unwrap()[0];
+    assert!(theta > 0.);
+
+    network
+        .set_property(&adaptive, "fix_theta".to_string(), 1.)
+        .unwrap();
+    network.run_for(100.);
+    assert_eq!(
+        network
+            .get_property(&adaptive, "theta".to_string())
+            .unwrap()[0],
+        theta
+    );
+    let json: ThresholdAdaptation =
+        serde_json::from_str(r#"{"theta_plus": 1, "property": "v_reset"}"#).unwrap();
+    assert_eq!(json.tau_theta, 1e7);
+    network.add_threshold_adaptation(&plain, &json).unwrap();
+    assert!(network
+        .add_threshold_adaptation(&hh, &ThresholdAdaptation::new(1., 0.))
+        .is_err());
+
+    // Izhikevich adapts its threshold current, its v_th being the spike peak
+    let mut params = Parameters::new();
+    params.insert("i_e".to_string(), 10.);
+    let iz_adaptive = adaptation
+        .attach(Box::new(izhikevich::Model::new(&params)))
+        .unwrap();
+    let mut counts = Vec::new();
+    for mut model in vec![Box::new(izhikevich::Model::new(&params)), iz_adaptive] {
+        let mut t = 0.;
+        let mut count = 0;
+        while t < 500. {
+            if let NeuronActivity::Fires(_) = model.update(t) {
+                count += 1;
+            }
+            t += Network::resolution();
+        }
+        assert_eq!(model.get_property("v_th".to_string()).unwrap(), 30.);
+        counts.push(count);
+    }
+    assert!(counts[1] > 0 && counts[1] < counts[0]);
+}
+
+#[test]
+fn synaptic_scaling() {
+    use wheatnnleek::connections::{static_connection, PostSynapticEffect};
+    use wheatnnleek::connectors::all_to_all;
+    use wheatnnleek::homeostasis::SynapticScaling;
+    use wheatnnleek::models::NeuronType;
+    use wheatnnleek::network::Network;
+    use wheatnnleek::Parameters;
+    let mut network = Network::new();
+    let input = network
+        .create(2, NeuronType::StaticPoisson, &Parameters::new())
+        .unwrap();
+    let output = network
+        .create_model(1, "iaf_psc_delta", &Parameters::new())
+        .unwrap();
+    let mut params = Parameters::new();
+    params.insert("weight".to_string(), 20.);
+    let ids = network.connect(
+        &input,
+        &output,
+        &all_to_all::Connector::default(),
+        &static_connection::Connection::new(&params, PostSynapticEffect::Excitatory),
+    );
+    network.set_rates(&input, vec![100., 100.]).unwrap();
+    assert!(network
+        .add_synaptic_scaling(SynapticScaling::new(ids.clone(), 0., 100.))
+        .is_err());
+    assert!(network
+        .add_synaptic_scaling(SynapticScaling::new(vec![99], 5., 100.))
+        .is_err());
+
+    // the output fires far above the target rate, so its inputs weaken
+    network
+        .add_synaptic_scaling(SynapticScaling::new(ids.clone(), 5., 100.))
+        .unwrap();
+    network.run_for(90.);
+    assert_eq!(network.get_conn_info_by_id(ids[0]).weight, 20.);
+    network.run_for(10.);
+    let weight = network.get_conn_info_by_id(ids[0]).weight;
+    assert!(weight < 20.);
+    assert_eq!(network.get_conn_info_by_id(ids[1]).weight, weight);
+
+    // a silent output strengthens its inputs
+    network.set_rates(&input, vec![0., 0.]).unwrap();
+    network.run_for(200.);
+    assert!(network.get_conn_info_by_id(ids[0]).weight > weight);
+    network.clear_synaptic_scalings();
+}
+
+#[test]
+fn plasticity_switch() {
+    use wheatnnleek::connections::{stdp_connection, PostSynapticEffect};
+    use wheatnnleek::connectors::all_to_all;
+    use wheatnnleek::network::Network;
+    use wheatnnleek::Parameters;
+    let mut network = Network::new();
+    let pre = network
+        .create_model(1, "spike_generator", &Parameters::new())
+        .unwrap();
+    let post = network
+        .create_model(1, "spike_generator", &Parameters::new())
+        .unwrap();
+    let mut params = Parameters::new();
+    params.insert("weight".to_string(), 0.5);
+    let connect = |network: &mut Network| {
+        network.connect(
+            &pre,
+            &post,
+            &all_to_all::Connector::default(),
+            &stdp_connection::Connection::new(&params, PostSynapticEffect::Excitatory),
+        )
+    };
+    let learning = connect(&mut network);
+    let frozen = connect(&mut network);
+    // pre before post potentiates once the post-synaptic trace is up
+    let pairs = |network: &mut Network| {
+        let start = network.current_time();
+        network.set_spike_times(&pre, &[vec![start + 4.]]).unwrap();
+        network
+            .set_spike_times(&post, &[vec![start + 3., start + 5.]])
+            .unwrap();
+        network.run_for(10.);
+    };
+    let weight = |network: &Network, ids: &[usize]| network.get_conn_info_by_id(ids[0]).weight;
+
+    network.set_plasticity(Some(&frozen), false).unwrap();
+    assert!(network.plasticity(learning[0]) && !network.plasticity(frozen[0]));
+    pairs(&mut network);
+    assert!(weight(&network, &learning) > 0.5);
+    assert_eq!(weight(&network, &frozen), 0.5);
+    assert!(network.set_plasticity(Some(&[99]), false).is_err());
+
+    network.set_plasticity(None, false).unwrap();
+    let created_frozen = connect(&mut network);
+    assert!(!network.plasticity(created_frozen[0]));
+    let learned = weight(&network, &learning);
+    pairs(&mut network);
+    assert_eq!(weight(&network, &learning), learned);
+
+    network.set_plasticity(None, true).unwrap();
+    pairs(&mut network);
+    assert!(weight(&network, &learning) > learned);
+    assert!(weight(&network, &frozen) > 0.5);
+}
+
+#[test]
+fn reset_network_state() {
+    use wheatnnleek::connections::{stdp_connection, PostSynapticEffect};
+    use wheatnnleek::connectors::all_to_all;
+    use wheatnnleek::network::{Network, ResetOptions};
+    use wheatnnleek::Parameters;
+    let mut network = Network::new();
+    let input = network
+        .create_model(1, "inhomogeneous_poisson_generator", &Parameters::new())
+        .unwrap();
+    let mut params = Parameters::new();
+    params.insert("i_e".to_string(), 30.);
+    let cb = network
+        .create_model(1, "ConductionBasedAdaptiveThresholdLIF", &params)
+        .unwrap();
+    let adex = network.create_model(1, "AdEx", &Parameters::new()).unwrap();
+    let ids = network.connect(
+        &input,
+        &cb,
+        &all_to_all::Connector::default(),
+        &stdp_connection::Connection::default(),
+    );
+    network.connect(
+        &input,
+        &adex,
+        &all_to_all::Connector::default(),
+        &stdp_connection::Connection::new(&Parameters::new(), PostSynapticEffect::Excitatory),
+    );
+    network.set_rates(&input, vec![1000.]).unwrap();
+    network.run_for(200.);
+    let get = |network: &Network, pop, name: &str| {
+        network.get_property(pop, name.to_string()).unwrap()[0]
+    };
+    let theta = get(&network, &cb, "theta");
+    assert!(theta > 0.);
+    assert!(get(&network, &adex, "w") != 0.);
+    let weight = network.get_conn_info_by_id(ids[0]).weight;
+
+    network.reset_state();
+    assert_eq!(get(&network, &cb, "v"), get(&network, &cb, "e_l"));
+    assert_eq!(get(&network, &cb, "ge"), 0.);
+    assert_eq!(get(&network, &cb, "theta"), theta);
+    assert_eq!(get(&network, &adex, "v"), get(&network, &adex, "e_l"));
+    assert_eq!(get(&network, &adex, "w"), 0.);
+    assert_eq!(get(&network, &adex, "g_ex"), 0.);
+    assert_eq!(network.get_conn_info_by_id(ids[0]).weight, weight);
+    assert_eq!(network.current_time(), 200.);
+
+    network.reset_state_with(&ResetOptions {
+        reset_thresholds: true,
+        rewind_clock: true,
+    });
+    assert_eq!(get(&network, &cb, "theta"), 0.);
+    assert_eq!(network.current_time(), 0.);
+    network.run_for(20.);
+    assert_eq!(network.current_time(), 20.);
+
+    // models restore the initial state they were configured with
+    let configured = |pairs: &[(&str, f64)]| {
+        pairs
+            .iter()
+            .map(|&(k, v)| (k.to_string(), v))
+            .collect::<Parameters>()
+    };
+    let hh = network
+        .create_model(
+            1,
+            "HodgkinHuxley",
+            &configured(&[("v_m", -70.), ("m", 0.05), ("i_e", 10.)]),
+        )
+        .unwrap();
+    let iz = network
+        .create_model(1, "Izhikevich", &configured(&[("v_m", -60.), ("i_e", 10.)]))
+        .unwrap();
+    let adex_w = network
+        .create_model(1, "AdEx", &configured(&[("v_m", -65.), ("w", 5.)]))
+        .unwrap();
+    network.run_for(50.);
+    network.reset_state();
+    assert_eq!(get(&network, &hh, "v"), -70.);
+    assert_eq!(get(&network, &hh, "m"), 0.05);
+    assert_eq!(get(&network, &iz, "v"), -60.);
+    assert_eq!(get(&network, &iz, "u"), -12.);
+    assert_eq!(get(&network, &adex_w, "v"), -65.);
+    assert_eq!(get(&network, &adex_w, "w"), 5.);
+}
+
+#[test]
+fn event_driven_engine() {
+    use wheatnnleek::connections::{static_connection, PostSynapticEffect};
+    use wheatnnleek::connectors::all_to_all;
+    use wheatnnleek::network::{Engine, Network};
+    use wheatnnleek::Parameters;
+    // the other tests may only set the resolution to this value
+    Network::set_resolution(0.8);
+    let simulate = |engine: Engine| {
+        let mut network = Network::new();
+        network.set_engine(engine);
+        let input = network
+            .create_model(2, "spike_generator", &Parameters::new())
+            .unwrap();
+        network
+            .set_spike_times(&input, &[vec![12., 13.6, 80.], vec![40., 160.8]])
+            .unwrap();
+        let mut params = Parameters::new();
+        params.insert("i_e".to_string(), 400.);
+        let tonic = network.create_model(1, "iaf_psc_delta", &params).unwrap();
+        let exp = network
+            .create_model(3, "iaf_psc_exp", &Parameters::new())
+            .unwrap();
+        let alpha = network
+            .create_model(2, "iaf_psc_alpha", &Parameters::new())
+            .unwrap();
+        let cond = network
+            .create_model(1, "iaf_cond_exp", &Parameters::new())
+            .unwrap();
+        let connect = |network: &mut Network, pre, post, weight: f64| {
+            let mut params = Parameters::new();
+            params.insert("weight".to_string(), weight);
+            let ids = network.connect(
+                pre,
+                post,
+                &all_to_all::Connector::default(),
+                &static_connection::Connection::new(&params, PostSynapticEffect::Excitatory),
+            );
+            // negative weights are drawn at random when connecting
+            for id in ids {
+                network.set_weight_by_conn_id(id, weight);
+            }
+        };
+        connect(&mut network, &input, &exp, 4000.);
+        connect(&mut network, &tonic, &alpha, 300.);
+        connect(&mut network, &exp, &alpha, 1500.);
+        connect(&mut network, &alpha, &exp, -800.);
+        connect(&mut network, &alpha, &tonic, 2.);
+        connect(&mut network, &exp, &cond, 20.);
+        for pop in [&tonic, &exp, &alpha, &cond].iter() {
+            network.record_spikes(*pop).unwrap();
+        }
+        network.run_for(200.);
+        network.run_for(40.);
+        let records = network.get_spike_records();
+        let v = network.get_property(&exp, "v".to_string()).unwrap();
+        (records, v)
+    };
+    let (time_driven, v_time) = simulate(Engine::TimeDriven);
+    let (event_driven, v_event) = simulate(Engine::EventDriven);
+    let spikes: usize = time_driven
+        .iter()
+        .map(|(_, runs)| runs.iter().map(|r| r.len()).sum::<usize>())
+        .sum();
+    assert!(spikes > 10);
+    assert_eq!(time_driven.len(), event_driven.len());
+    for ((id, runs), (event_id, event_runs)) in time_driven.iter().zip(&event_driven) {
+        assert_eq!(id, event_id);
+        assert_eq!(runs.len(), event_runs.len());
+        for (run, event_run) in runs.iter().zip(event_runs) {
+            assert_eq!(run.len(), event_run.len());
+            for (t, event_t) in run.iter().zip(event_run) {
+                assert!((t - event_t).abs() < 1e-9);
+            }
+        }
+    }
+    for (v, event_v) in v_time.iter().zip(&v_event) {
+        assert!((v - event_v).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn neuron_groups() {
+    use wheatnnleek::connections::{static_connection, PostSynapticEffect};
+    use wheatnnleek::connectors::all_to_all;
+    use wheatnnleek::network::{Engine, Network};
+    use wheatnnleek::Parameters;
+    // the other tests may only set the resolution to this value
+    Network::set_resolution(0.8);
+    let simulate = |grouped: bool, engine: Engine| {
+        let mut network = Network::new();
+        network.set_engine(engine);
+        let input = network
+            .create_model(2, "spike_generator", &Parameters::new())
+            .unwrap();
+        network
+            .set_spike_times(&input, &[vec![12., 13.6, 80.], vec![40., 160.8]])
+            .unwrap();
+        let mut params = Parameters::new();
+        params.insert("i_e".to_string(), 400.);
+        let create = |network: &mut Network, size, model: &str, params: &Parameters| {
+            if grouped {
+                network.create_group(size, model, params).unwrap()
+            } else {
+                network.create_model(size, model, params).unwrap()
+            }
+        };
+        let tonic = create(&mut network, 1, "iaf_psc_delta", &params);
+        let exp = create(&mut network, 3, "iaf_psc_exp", &Parameters::new());
+        let alpha = create(&mut network, 2, "iaf_psc_alpha", &Parameters::new());
+        let connect = |network: &mut Network, pre, post, weight: f64| {
+            let mut params = Parameters::new();
+            params.insert("weight".to_string(), weight);
+            let ids = network.connect(
+                pre,
+                post,
+                &all_to_all::Connector::default(),
+                &static_connection::Connection::new(&params, PostSynapticEffect::Excitatory),
+            );
+            for id in ids {
+                network.set_weight_by_conn_id(id, weight);
+            }
+        };
+        connect(&mut network, &input, &exp, 4000.);
+        connect(&mut network, &tonic, &alpha, 300.);
+        connect(&mut network, &exp, &alpha, 1500.);
+        connect(&mut network, &alpha, &exp, -800.);
+        for pop in [&tonic, &exp, &alpha].iter() {
+            network.record_spikes(*pop).unwrap();
+        }
+        network.run_for(200.);
+        let records = network.get_spike_records();
+        let v = network.get_property(&alpha, "v".to_string()).unwrap();
+        (records, v)
+    };
+    let (single, v_single) = simulate(false, Engine::TimeDriven);
+    assert!(single.iter().map(|(_, runs)| runs[0].len()).sum::<usize>() > 10);
+    for engine in [Engine::TimeDriven, Engine::EventDriven].iter() {
+        let (grouped, v_grouped) = simulate(true, *engine);
+        assert_eq!(single, grouped);
+        assert_eq!(v_single, v_grouped);
+    }
+
+    let mut network = Network::new();
+    assert!(network
+        .create_group(2, "iaf_cond_exp", &Parameters::new())
+        .is_err());
+    let input = network
+        .create_group(2, "StaticPoisson", &Parameters::new())
+        .unwrap();
+    let lif = network
+        .create_group(2, "iaf_psc_exp", &Parameters::new())
+        .unwrap();
+    network.set_rates(&input, vec![0., 2000.]).unwrap();
+    network.connect(
+        &input,
+        &lif,
+        &all_to_all::Connector::default(),
+        &static_connection::Connection::default(),
+    );
+    network.record_spikes(&input).unwrap();
+    network.run_for(20.);
+    assert_eq!(network.spike_counts(&input)[0], 0);
+    assert!(network.spike_counts(&input)[1] > 0);
+    let get = |network: &Network, name: &str| network.get_property(&lif, name.to_string()).unwrap();
+    assert!(get(&network, "syn_ex")[0] > 0.);
+    network
+        .set_property(&lif, "v_th".to_string(), -60.)
+        .unwrap();
+    assert_eq!(get(&network, "v_th"), vec![-60., -60.]);
+    assert!(network
+        .set_property(&lif, "tau_m".to_string(), 20.)
+        .is_err());
+    network.reset_state();
+    assert_eq!(get(&network, "v"), vec![-70., -70.]);
+    assert_eq!(get(&network, "syn_ex"), vec![0., 0.]);
+}
+
+#[test]
+fn step_hooks() {
+    use std::sync::{Arc, Mutex};
+    use wheatnnleek::hooks::{HookTrigger, RunControl};
+    use wheatnnleek::network::{Engine, Network};
+    use wheatnnleek::Parameters;
+    let mut network = Network::new();
+    let mut params = Parameters::new();
+    params.insert("i_e".to_string(), 400.);
+    let lif = network.create_model(1, "iaf_psc_exp", &params).unwrap();
+    let generator = network
+        .create_model(1, "spike_generator", &Parameters::new())
+        .unwrap();
+    network.record_spikes(&lif).unwrap();
+
+    // the callback switches the input current off after 40 ms
+    let mut times = Vec::new();
+    let reached = network
+        .run_with_callback(80., 40., |network, t| {
+            times.push(t);
+            network.set_property(&lif, "i_e".to_string(), 0.).unwrap();
+            RunControl::Continue
+        })
+        .unwrap();
+    assert_eq!(reached, 80.);
+    assert_eq!(times, vec![40., 80.]);
+    let spikes = &network.get_spike_records()[0].1[0];
+    assert!(!spikes.is_empty());
+    assert!(spikes.iter().all(|&t| t < 40.));
+    assert!(network
+        .run_with_callback(40., 0., |_, _| RunControl::Continue)
+        .is_err());
+
+    // neurons cannot be added or removed during a run
+    network.set_engine(Engine::EventDriven);
+    network
+        .run_with_callback(4., 2., |network, _| {
+            assert!(network
+                .create_model(1, "iaf_psc_exp", &Parameters::new())
+                .is_err());
+            assert!(network.clear().is_err());
+            RunControl::Continue
+        })
+        .unwrap();
+    network.set_engine(Engine::TimeDriven);
+
+    // a hook on the spikes of the generator stops the run
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let recorded = calls.clone();
+    let id = network
+        .add_step_hook(
+            HookTrigger::Spikes(vec![generator.get(0).unwrap() as usize]),
+            Box::new(move |_, t| {
+                recorded.lock().unwrap().push(t);
+                RunControl::Stop
+            }),
+        )
+        .unwrap();
+    network.set_spike_times(&generator, &[vec![100.]]).unwrap();
+    network.set_engine(Engine::EventDriven);
+    let reached = network.run_for(120.);
+    assert_eq!(*calls.lock().unwrap(), vec![reached]);
+    assert!(reached > 100. && reached < 102.);
+    network.remove_step_hook(id).unwrap();
+    assert!(network.remove_step_hook(id).is_err());
+    assert!(network
+        .add_step_hook(
+            HookTrigger::Every(-1.),
+            Box::new(|_, _| RunControl::Continue)
+        )
+        .is_err());
+    assert!(network
+        .add_step_hook(
+            HookTrigger::Spikes(vec![7]),
+            Box::new(|_, _| RunControl::Continue)
+        )
+        .is_err());
+}
+
+#[test]
+fn run_until_conditions() {
+    use wheatnnleek::hooks::StopCondition;
+    use wheatnnleek::network::Network;
+    use wheatnnleek::Parameters;
+    let mut network = Network::new();
+    let mut params = Parameters::new();
+    params.insert("i_e".to_string(), 400.);
+    let lif = network.create_model(2, "iaf_psc_exp", &params).unwrap();
+    network.record_spikes(&lif).unwrap();
+
+    let first = network
+        .run_until(200., &StopCondition::fires(&lif))
+        .unwrap()
+        .unwrap();
+    assert_eq!(network.current_time(), first);
+    let spikes = &network.get_spike_records()[0].1[0];
+    assert!(!spikes.is_empty());
+    assert!(spikes.iter().all(|&t| t <= first));
+
+    // the count starts with each run
+    let reached = network
+        .run_until(200., &StopCondition::spike_count(&lif, 6))
+        .unwrap()
+        .unwrap();
+    let fired: usize = network
+        .get_spike_records()
+        .iter()
+        .map(|(_, records)| records.last().unwrap().len())
+        .sum();
+    assert!(fired >= 6 && reached > first);
+
+    network.reset_state();
+    let nid = lif.get(0).unwrap() as usize;
+    let crossed = network
+        .run_until(200., &StopCondition::crosses(nid, "v", -60.))
+        .unwrap()
+        .unwrap();
+    assert!(network.get_property(&lif, "v".to_string()).unwrap()[0] >= -60.);
+    let falling = StopCondition::Crosses {
+        neuron: nid,
+        property: "v".to_string(),
+        value: -65.,
+        falling: true,
+    };
+    assert!(network.run_until(200., &falling).unwrap().unwrap() > crossed);
+
+    // without input the neurons stay quiet until the end
+    network.set_property(&lif, "i_e".to_string(), 0.).unwrap();
+    let start = network.current_time();
+    assert_eq!(
+        network.run_until(40., &StopCondition::fires(&lif)).unwrap(),
+        None
+    );
+    assert!((network.current_time() - start - 40.).abs() < 1e-9);
+    assert!(network
+        .run_until(40., &StopCondition::crosses(nid, "w", 0.))
+        .is_err());
+    assert!(network
+        .run_until(40., &StopCondition::Fires { neurons: vec![7] })
+        .is_err());
+}
+
+#[test]
+fn failed_population_setters_change_nothing() {
+    use wheatnnleek::models::inhomogeneous_poisson::RateSchedule;
+    use wheatnnleek::models::Integration;
+    use wheatnnleek::network::Network;
+    use wheatnnleek::populations::Population;
+    use wheatnnleek::Parameters;
+    let mut params = Parameters::new();
+    params.insert("i_e".to_string(), 10.);
+    let simulate = |method: Option<Integration>| {
+        let mut network = Network::new();
+        let iz = network.create_model(1, "Izhikevich", &params).unwrap();
+        let iaf = network.create_model(1, "IAF", &params).unwrap();
+        let mixed = Population::new(99, &[iz.get(0).unwrap(), iaf.get(0).unwrap()]);
+        if let Some(method) = method {
+            // IAF does not support RungeKutta45, so Izhikevich is left as is
+            assert!(network.set_integration(&mixed, method).is_err());
+        }
+        network.run_for(20.);
+        network.get_property(&iz, "v".to_string()).unwrap()[0]
+    };
+    assert_eq!(simulate(Some(Integration::RungeKutta45)), simulate(None));
+
+    // the second generator repeats every 5 ms and rejects a spike at 8 ms
+    let mut network = Network::new();
+    let first = network
+        .create_model(1, "spike_generator", &Parameters::new())
+        .unwrap();
+    let mut periodic = Parameters::new();
+    periodic.insert("period".to_string(), 5.);
+    let second = network
+        .create_model(1, "spike_generator", &periodic)
+        .unwrap();
+    let generators = Population::new(99, &[first.get(0).unwrap(), second.get(0).unwrap()]);
+    network
+        .set_spike_times(&generators, &[vec![1.], vec![1.]])
+        .unwrap();
+    assert!(network
+        .set_spike_times(&generators, &[vec![8.], vec![8.]])
+        .is_err());
+    network.record_spikes(&first).unwrap();
+    network.run_for(10.);
+    let spikes = &network.get_spike_records()[0].1[0];
+    assert_eq!(spikes.len(), 1);
+    assert!(spikes[0] < 2.);
+
+    // the rate of the Poisson generator stays at 0 Hz
+    let input = network
+        .create_model(1, "inhomogeneous_poisson_generator", &Parameters::new())
+        .unwrap();
+    let mixed = Population::new(99, &[input.get(0).unwrap(), first.get(0).unwrap()]);
+    let schedule = RateSchedule::Constant { rate: 1e4 };
+    assert!(network.set_rate_schedule(&mixed, &schedule).is_err());
+    network.run_for(5.);
+    assert_eq!(
+        network.get_property(&input, "freq".to_string()).unwrap(),
+        vec![0.]
+    );
+}